@@ -10,12 +10,14 @@
 extern crate proc_macro;
 
 use proc_macro::{Span, TokenStream};
-use quote::quote;
 use quote::ToTokens;
+use quote::{quote, quote_spanned};
 use syn::parse::discouraged::Speculative;
-use syn::parse::{Parse, ParseStream, Result};
+use syn::parse::{Parse, ParseStream, Parser, Result};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::visit_mut::{self, VisitMut};
 
 struct ParsableAttribute {
     pub attributes: Vec<syn::Attribute>,
@@ -29,23 +31,126 @@ impl Parse for ParsableAttribute {
     }
 }
 
-fn simplify_path(mut path: syn::Path) -> syn::Path {
+fn simplify_path(mut path: syn::Path) -> Result<syn::Path> {
+    if path.segments.len() < 2 || !path.segments.last().is_some_and(|i| i.ident == "label") {
+        return Err(syn::Error::new_spanned(
+            &path,
+            "label attributes have to be written as the path to the label followed by `::label`, like `#[my_label::label]`",
+        ));
+    }
+
     // replace ::annotate with ::add in the path.
     // It would be cleaner to remove ::annotate entirely, but couldn't find
     // a way to do that. .pop() retains the ::.
     if let Some(i) = path.segments.last_mut() {
-        assert_eq!(i.ident.to_string(), "label");
         let new_ident = syn::Ident::new("add", i.span());
         i.ident = new_ident;
     }
 
-    path
+    Ok(path)
+}
+
+/// Joins the `#[doc = ".."]` attributes (doc comments) into a single string,
+/// removing the indentation common to all lines.
+fn doc_string(attrs: &[syn::Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|i| i.path.is_ident("doc"))
+        .filter_map(|i| match i.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(s),
+                ..
+            })) => Some(s.value()),
+            _ => None,
+        })
+        .flat_map(|i| i.split('\n').map(str::to_string).collect::<Vec<_>>())
+        .collect();
+
+    let indent = lines
+        .iter()
+        .filter(|i| !i.trim().is_empty())
+        .map(|i| i.len() - i.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|i| i.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+/// The arguments of a label attribute as `(key, value, span of the value)`.
+type Metadata = Vec<(String, String, proc_macro2::Span)>;
+
+/// Converts the arguments of a label attribute to `(key, value)` pairs.
+/// Arguments are either `key = literal`, or a single `key` which gets an empty value.
+fn metadata(args: impl IntoIterator<Item = syn::NestedMeta>) -> Result<Metadata> {
+    let key = |path: &syn::Path| {
+        path.get_ident().map(|i| i.to_string()).ok_or_else(|| {
+            syn::Error::new_spanned(
+                path,
+                "expected a single identifier as key, like `#[my_label::label(key = \"value\")]`",
+            )
+        })
+    };
+
+    args.into_iter()
+        .map(|i| match i {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                Ok((key(&path)?, String::new(), path.span()))
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(i)) => {
+                let value = match &i.lit {
+                    syn::Lit::Str(s) => s.value(),
+                    syn::Lit::Char(c) => c.value().to_string(),
+                    syn::Lit::Bool(b) => b.value.to_string(),
+                    other => other.to_token_stream().to_string(),
+                };
+                Ok((key(&i.path)?, value, i.lit.span()))
+            }
+            other => Err(syn::Error::new_spanned(
+                other,
+                "expected `key = value` or `key` as argument to a label",
+            )),
+        })
+        .collect()
+}
+
+/// The metadata of a label attribute on the item, which is not the attribute being expanded.
+fn attribute_metadata(attr: &syn::Attribute) -> Result<Metadata> {
+    if attr.tokens.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match attr.parse_meta()? {
+        syn::Meta::List(i) => metadata(i.nested),
+        other => Err(syn::Error::new_spanned(
+            other,
+            "expected arguments to a label between parentheses",
+        )),
+    }
+}
+
+fn metadata_tokens(metadata: &Metadata) -> proc_macro2::TokenStream {
+    let keys = metadata.iter().map(|(key, _, _)| key);
+    let values = metadata.iter().map(|(_, value, _)| value);
+    quote! {
+        &[#((#keys, #values)),*]
+    }
 }
 
 enum Item {
     Func(syn::ItemFn),
     Static(syn::ItemStatic),
     Const(syn::ItemConst),
+    /// A unit struct, like `struct Foo;`, for labels of a trait made with `#[label::registry]`.
+    Struct(syn::ItemStruct),
+    /// An implementation of a trait for a unit struct, like `impl Plugin for Foo {}`. The path to
+    /// the struct is kept separately, its last segment is the name of the item.
+    Impl(syn::ItemImpl, syn::Path),
 }
 
 impl Item {
@@ -54,6 +159,18 @@ impl Item {
             Item::Func(i) => &i.sig.ident,
             Item::Static(i) => &i.ident,
             Item::Const(i) => &i.ident,
+            Item::Struct(i) => &i.ident,
+            Item::Impl(_, path) => &path.segments.last().expect("a path has a segment").ident,
+        }
+    }
+
+    pub fn vis(&self) -> syn::Visibility {
+        match self {
+            Item::Func(i) => i.vis.clone(),
+            Item::Static(i) => i.vis.clone(),
+            Item::Const(i) => i.vis.clone(),
+            Item::Struct(i) => i.vis.clone(),
+            Item::Impl(..) => syn::Visibility::Inherited,
         }
     }
 
@@ -62,6 +179,8 @@ impl Item {
             Item::Func(i) => i.attrs.clone(),
             Item::Static(i) => i.attrs.clone(),
             Item::Const(i) => i.attrs.clone(),
+            Item::Struct(i) => i.attrs.clone(),
+            Item::Impl(i, _) => i.attrs.clone(),
         }
     }
 
@@ -70,6 +189,8 @@ impl Item {
             Item::Func(i) => i.attrs = attrs,
             Item::Static(i) => i.attrs = attrs,
             Item::Const(i) => i.attrs = attrs,
+            Item::Struct(i) => i.attrs = attrs,
+            Item::Impl(i, _) => i.attrs = attrs,
         }
     }
 }
@@ -80,8 +201,142 @@ impl ToTokens for Item {
             Item::Func(i) => i.to_tokens(tokens),
             Item::Static(i) => i.to_tokens(tokens),
             Item::Const(i) => i.to_tokens(tokens),
+            Item::Struct(i) => i.to_tokens(tokens),
+            Item::Impl(i, _) => i.to_tokens(tokens),
+        }
+    }
+}
+
+/// The kind of a visibility, which is the name of its variant of `label::Visibility`.
+fn visibility_kind(vis: &syn::Visibility) -> &'static str {
+    match vis {
+        syn::Visibility::Public(_) => "Public",
+        syn::Visibility::Crate(_) => "Crate",
+        syn::Visibility::Restricted(i) if i.path.is_ident("crate") => "Crate",
+        syn::Visibility::Restricted(i) if i.path.is_ident("super") => "Super",
+        syn::Visibility::Restricted(i) if i.path.is_ident("self") => "Private",
+        syn::Visibility::Restricted(_) => "Restricted",
+        syn::Visibility::Inherited => "Private",
+    }
+}
+
+/// The `label::Visibility` of an item declared with `vis`.
+fn visibility_tokens(vis: &syn::Visibility) -> proc_macro2::TokenStream {
+    let kind = syn::Ident::new(visibility_kind(vis), proc_macro2::Span::call_site());
+    match vis {
+        syn::Visibility::Restricted(i) if kind == "Restricted" => {
+            let path = i.path.to_token_stream().to_string().replace(' ', "");
+            quote! { label::Visibility::Restricted(#path) }
+        }
+        _ => quote! { label::Visibility::#kind },
+    }
+}
+
+/// Finds `impl Trait` types, which can't be part of a function pointer.
+#[derive(Default)]
+struct ImplTrait(bool);
+
+impl<'ast> Visit<'ast> for ImplTrait {
+    fn visit_type_impl_trait(&mut self, _: &'ast syn::TypeImplTrait) {
+        self.0 = true;
+    }
+}
+
+/// Finds which lifetimes are used in a type: `'static`, or other (possibly elided) lifetimes.
+#[derive(Default)]
+struct LifetimeUse {
+    statics: bool,
+    others: bool,
+}
+
+impl<'ast> Visit<'ast> for LifetimeUse {
+    fn visit_lifetime(&mut self, i: &'ast syn::Lifetime) {
+        if i.ident == "static" {
+            self.statics = true;
+        } else {
+            self.others = true;
+        }
+    }
+
+    fn visit_type_reference(&mut self, i: &'ast syn::TypeReference) {
+        if i.lifetime.is_none() {
+            self.others = true;
+        }
+        visit::visit_type_reference(self, i);
+    }
+}
+
+/// The function pointer type of a function, like `fn(u8) -> u8`. `None` for functions which
+/// don't have a single pointer type, like generic functions.
+///
+/// Also `None` for functions using both `'static` and other lifetimes, which can be more general
+/// than the pointer type of a label they are labeled with, like `fn(&u8) -> &'static u8` for a
+/// label expecting `for<'a> fn(&'a u8) -> &'a u8`. Lifetimes can be hidden in paths like `Item`
+/// for `Item<'_>`, so any argument counts as another lifetime.
+fn fn_pointer(sig: &syn::Signature) -> Option<proc_macro2::TokenStream> {
+    let generics = &sig.generics;
+    if sig.asyncness.is_some()
+        || sig.variadic.is_some()
+        || generics.where_clause.is_some()
+        || generics
+            .params
+            .iter()
+            .any(|i| !matches!(i, syn::GenericParam::Lifetime(l) if l.bounds.is_empty()))
+    {
+        return None;
+    }
+
+    let mut impl_trait = ImplTrait::default();
+    let mut lifetimes = LifetimeUse::default();
+    impl_trait.visit_return_type(&sig.output);
+    lifetimes.visit_return_type(&sig.output);
+    let mut inputs = Vec::new();
+    for i in &sig.inputs {
+        match i {
+            syn::FnArg::Typed(i) => {
+                impl_trait.visit_type(&i.ty);
+                lifetimes.visit_type(&i.ty);
+                inputs.push(&i.ty);
+            }
+            syn::FnArg::Receiver(_) => return None,
         }
     }
+    if impl_trait.0 || (lifetimes.statics && (lifetimes.others || !inputs.is_empty())) {
+        return None;
+    }
+
+    let lifetimes = generics.lifetimes();
+    let unsafety = &sig.unsafety;
+    let abi = &sig.abi;
+    let output = &sig.output;
+    Some(quote! {
+        for<#(#lifetimes),*> #unsafety #abi fn(#(#inputs),*) #output
+    })
+}
+
+/// Parses a static without a value, `static NAME: Type;`, which gets the value `<Type>::new()`.
+fn declared_static(input: ParseStream) -> Result<syn::ItemStatic> {
+    let attrs = input.call(syn::Attribute::parse_outer)?;
+    let vis = input.parse()?;
+    let static_token = input.parse()?;
+    let mutability = input.parse()?;
+    let ident = input.parse()?;
+    let colon_token = input.parse()?;
+    let ty: syn::Type = input.parse()?;
+    let semi_token = input.parse()?;
+
+    Ok(syn::ItemStatic {
+        attrs,
+        vis,
+        static_token,
+        mutability,
+        ident,
+        colon_token,
+        expr: Box::new(syn::parse_quote! { <#ty>::new() }),
+        ty: Box::new(ty),
+        eq_token: Default::default(),
+        semi_token,
+    })
 }
 
 impl Parse for Item {
@@ -104,36 +359,153 @@ impl Parse for Item {
             return Ok(Item::Const(i));
         }
 
-        Err(input.error("Expected either function definition, static variable or const variable."))
+        let tokens = input.fork();
+        if let Ok(i) = tokens.call(declared_static) {
+            input.advance_to(&tokens);
+            return Ok(Item::Static(i));
+        }
+
+        let tokens = input.fork();
+        if let Ok(i) = tokens.parse::<syn::ItemStruct>() {
+            if !matches!(i.fields, syn::Fields::Unit) || !i.generics.params.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &i.ident,
+                    "only unit structs can be labeled, like `struct Foo;`",
+                ));
+            }
+            input.advance_to(&tokens);
+            return Ok(Item::Struct(i));
+        }
+
+        let tokens = input.fork();
+        if let Ok(i) = tokens.parse::<syn::ItemImpl>() {
+            let path = match &*i.self_ty {
+                syn::Type::Path(ty)
+                    if ty.qself.is_none()
+                        && i.trait_.is_some()
+                        && i.generics.params.is_empty()
+                        && ty.path.segments.iter().all(|i| i.arguments.is_empty()) =>
+                {
+                    ty.path.clone()
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &i.self_ty,
+                        "only implementations of a trait for a unit struct can be labeled, like `impl Plugin for Foo {}`",
+                    ))
+                }
+            };
+            input.advance_to(&tokens);
+            return Ok(Item::Impl(i, path));
+        }
+
+        Err(input.error(
+            "labels can only be put on functions, statics, consts, unit structs and trait implementations",
+        ))
     }
 }
 
-#[proc_macro_attribute]
-#[doc(hidden)]
-/// DO NOT USE DIRECTLY! USE THROUGH CREATE_ANNOTATION
-pub fn __label(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let mut item = syn::parse_macro_input!(item as Item);
+/// Whether a path is written like the path of a label attribute, `my_label::label`.
+fn is_label_path(path: &syn::Path) -> bool {
+    path.segments.len() >= 2 && path.segments.last().is_some_and(|i| i.ident == "label")
+}
 
-    // other annotation attributes
-    let mut other_annotations = Vec::new();
-    // any other attribute present
-    let mut other_attrs = Vec::new();
-    for i in item.attrs() {
-        if let Some(ref lst) = i.path.segments.last() {
-            if &*lst.ident.to_string() == "label" {
-                other_annotations.push(simplify_path(i.path));
-                continue;
-            }
+/// The path of the attribute written in `text`, if it is the source code of one.
+/// Attributes behind `cfg_attr` only have their path and arguments as source code.
+fn attribute_path(text: &str) -> Option<syn::Path> {
+    match syn::parse_str::<ParsableAttribute>(text) {
+        Ok(i) => Some(i.attributes.into_iter().next()?.path),
+        Err(_) => Some(syn::parse_str::<syn::Meta>(text).ok()?.path().clone()),
+    }
+}
+
+/// Flattens tokens, leaving out the delimiters of groups.
+fn flatten(tokens: proc_macro2::TokenStream, res: &mut Vec<proc_macro2::TokenTree>) {
+    for i in tokens {
+        match i {
+            proc_macro2::TokenTree::Group(i) => flatten(i.stream(), res),
+            i => res.push(i),
         }
-        other_attrs.push(i);
     }
+}
 
-    // remove all label from the function's attributes
-    // but keep other attributes
-    item.set_attrs(other_attrs);
+/// Finds the path of the label attribute on `item_name` in the source code of a macro invocation,
+/// like `labeled!(#[my_label::label] my_fn);`. This is the last label path before the name of the
+/// item, leaving out the `other` label attributes which are still on the item. When the name isn't
+/// in the invocation, it can only contain a single label path.
+fn invocation_path(text: &str, item_name: &syn::Ident, other: &[String]) -> Option<syn::Path> {
+    use proc_macro2::TokenTree;
 
-    let item_name = item.name();
+    let mut tokens = Vec::new();
+    flatten(syn::parse_str(text).ok()?, &mut tokens);
+
+    let mut paths = Vec::new();
+    let mut name = None;
+    let mut index = 0;
+    while index < tokens.len() {
+        // the longest path starting at `index`
+        let mut end = index;
+        let mut path = String::new();
+        while let Some(TokenTree::Ident(i)) = tokens.get(end) {
+            path.push_str(&i.to_string());
+            end += 1;
+            match (tokens.get(end), tokens.get(end + 1)) {
+                (Some(TokenTree::Punct(a)), Some(TokenTree::Punct(b)))
+                    if a.as_char() == ':' && b.as_char() == ':' =>
+                {
+                    path.push_str("::");
+                    end += 2;
+                }
+                _ => break,
+            }
+        }
+
+        // paths starting with a metavariable like `$label::label` in the body of a macro can't be used.
+        let metavariable =
+            index > 0 && matches!(&tokens[index - 1], TokenTree::Punct(i) if i.as_char() == '$');
+
+        match syn::parse_str::<syn::Path>(&path) {
+            Ok(i)
+                if is_label_path(&i)
+                    && !metavariable
+                    && !other.contains(&i.to_token_stream().to_string()) =>
+            {
+                paths.push((index, i));
+            }
+            _ if name.is_none() && *item_name == path => name = Some(index),
+            _ => {}
+        }
+        index = end.max(index + 1);
+    }
+
+    match name {
+        Some(name) => paths
+            .into_iter()
+            .rev()
+            .find(|(index, _)| *index < name)
+            .map(|(_, i)| i),
+        None => {
+            let first = paths.first()?.1.to_token_stream().to_string();
+            if paths
+                .iter()
+                .all(|(_, i)| i.to_token_stream().to_string() == first)
+            {
+                paths.into_iter().map(|(_, i)| i).next()
+            } else {
+                None
+            }
+        }
+    }
+}
 
+/// The path to the `add` module of the label of the attribute being expanded, found in the source code.
+/// `other` are the other label attributes on the item, with the path to their `add` module.
+/// `custom_attribute` is set when the attribute is the custom attribute of a label, see `__label_attribute`.
+fn label_path(
+    item_name: &syn::Ident,
+    other: &[(syn::Path, Metadata)],
+    custom_attribute: bool,
+) -> Result<syn::Path> {
     // for the following, the feature
     // #![feature(proc_macro_quote)]
     // could be used together with syn and proc_macro::quote_span.
@@ -150,61 +522,482 @@ pub fn __label(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
         possible
     };
+    let texts: Vec<_> = spans.iter().filter_map(|i| i.source_text()).collect();
+
+    // The attribute itself, when it's written on the item, in the body of a macro or in `cfg_attr`.
+    // Any other attribute found is the attribute, when it's not written like a label attribute,
+    // or the attribute of a macro generating the item.
+    let mut other_attribute = None;
+    for path in texts.iter().rev().filter_map(|i| attribute_path(i)) {
+        if is_label_path(&path) {
+            return simplify_path(path);
+        }
+        other_attribute.get_or_insert(path);
+    }
+
+    // A custom attribute of a label, like `#[my_label::register]`, is the path to the `add` module
+    // of its label itself. It's only used when it's written on the item, and not on an item
+    // generated from the attribute with the name taken from somewhere else.
+    let written = item_name.span().unwrap().source_text() == Some(item_name.to_string());
+    if let Some(path) = texts.first().and_then(|i| attribute_path(i)) {
+        if custom_attribute && written {
+            return Ok(path);
+        }
+    }
 
-    let mut res = None;
-    for span in spans
+    // The invocation of a macro generating the item, when the attribute is passed to it.
+    let other: Vec<_> = other
+        .iter()
+        .map(|(path, _)| {
+            let mut path = path.clone();
+            if let Some(i) = path.segments.last_mut() {
+                i.ident = syn::Ident::new("label", i.ident.span());
+            }
+            path.to_token_stream().to_string()
+        })
+        .collect();
+    if let Some(path) = texts
         .iter()
-        .rev()
-        .map(|i| i.source_text())
-        .filter_map(|i| i)
+        .find_map(|i| invocation_path(i, item_name, &other))
     {
-        if let Ok(i) = syn::parse_str::<ParsableAttribute>(&span) {
-            res = Some(i);
-            break;
+        return simplify_path(path);
+    }
+
+    let message = format!(
+        "could not find the label of `{}`. Label attributes have to be written as the path to the label \
+        followed by `::label`, like `#[my_label::label]`, on the item or in the invocation of the macro \
+        generating it. Otherwise, use `#[label::labeled(path::to::my_label)]`",
+        item_name
+    );
+    Err(match other_attribute {
+        Some(path) => syn::Error::new_spanned(path, message),
+        None => syn::Error::new(item_name.span(), message),
+    })
+}
+
+#[proc_macro_attribute]
+#[doc(hidden)]
+/// DO NOT USE DIRECTLY! USE THROUGH CREATE_ANNOTATION
+pub fn __label(attr: TokenStream, item: TokenStream) -> TokenStream {
+    expand(attr, item, None, false)
+}
+
+#[proc_macro_attribute]
+#[doc(hidden)]
+/// DO NOT USE DIRECTLY! USE THROUGH THE `attribute` OPTION OF CREATE_ANNOTATION
+pub fn __label_attribute(attr: TokenStream, item: TokenStream) -> TokenStream {
+    expand(attr, item, None, true)
+}
+
+/// Labels an item with the label at the given path, like `#[my_label::label]` does for `my_label`.
+///
+/// ```ignore
+/// #[label::labeled(my_label, key = "value")]
+/// fn my_function() {}
+/// ```
+///
+/// `#[my_label::label]` finds the label it belongs to in the source code around it, which is not
+/// possible for items generated by some macros. Those can use this attribute instead.
+#[proc_macro_attribute]
+pub fn labeled(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let arguments = |input: ParseStream| {
+        let path: syn::Path = input.parse()?;
+        if !input.is_empty() {
+            input.parse::<syn::Token![,]>()?;
+        }
+        Ok((path, input.parse::<proc_macro2::TokenStream>()?))
+    };
+
+    match arguments.parse(attr) {
+        Ok((mut path, metadata)) => {
+            path.segments
+                .push(syn::Ident::new("add", path.span()).into());
+            expand(metadata.into(), item, Some(path), false)
+        }
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Converts a name like `HttpHandler` to `http_handler`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut res = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            // a new word starts after a lowercase letter, or at the end of an acronym like `HTTP`.
+            let previous = chars[index - 1];
+            let next_lowercase = chars.get(index + 1).is_some_and(|i| i.is_lowercase());
+            if previous != '_' && (!previous.is_uppercase() || next_lowercase) {
+                res.push('_');
+            }
         }
+        res.extend(c.to_lowercase());
     }
+    res
+}
 
-    let path: syn::Path = if let Some(res) = res {
-        res.attributes[0].path.clone()
+/// Makes a label of a trait, with which unit structs and implementations of the trait are labeled.
+///
+/// ```ignore
+/// #[label::registry]
+/// pub trait Plugin {
+///     fn name(&self) -> &str;
+/// }
+///
+/// #[plugin::label]
+/// struct Logger;
+///
+/// impl Plugin for Logger {
+///     fn name(&self) -> &str {
+///         "logger"
+///     }
+/// }
+///
+/// for plugin in plugin::iter() {
+///     println!("{}", plugin.name());
+/// }
+/// ```
+///
+/// The label can't have the same name as the trait, so it is named after the trait in snake case,
+/// like `plugin` for `Plugin`. Another name can be given as argument, like `#[label::registry(plugins)]`.
+/// Besides this, it is the same as a label made with `create_label!(pub static plugin: dyn Plugin + Sync;)`,
+/// and labeled items are registered as a `&'static (dyn Plugin + Sync)`.
+///
+/// The label can be put on a unit struct, or on the implementation of the trait for a unit struct,
+/// like `#[plugin::label] impl Plugin for Logger {}`.
+#[proc_macro_attribute]
+pub fn registry(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item_trait = syn::parse_macro_input!(item as syn::ItemTrait);
+    let trait_name = &item_trait.ident;
+    let name = if attr.is_empty() {
+        syn::Ident::new(&snake_case(&trait_name.to_string()), trait_name.span())
     } else {
-        unreachable!()
+        syn::parse_macro_input!(attr as syn::Ident)
+    };
+
+    if !item_trait.generics.params.is_empty() {
+        return syn::Error::new_spanned(
+            &item_trait.generics,
+            "registries can only be made of traits without generic parameters",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let doc = format!(
+        "The items labeled with this label, which implement [`{}`].",
+        trait_name
+    );
+    let options = Options {
+        attrs: vec![syn::parse_quote! { #[doc = #doc] }],
+        ..Options::default()
+    };
+    let definition = Definition::Static {
+        name,
+        var_type: syn::parse_quote! { (dyn #trait_name + Sync) },
+        mutable: false,
+    };
+    let module = label_module(&options, &item_trait.vis, &definition);
+
+    let result = quote! {
+        #item_trait
+
+        #module
+    };
+
+    result.into()
+}
+
+/// Labels `item` with the label of which the `add` module is at `callpath`, or with the label of
+/// the attribute being expanded.
+fn expand(
+    attr: TokenStream,
+    item: TokenStream,
+    callpath: Option<syn::Path>,
+    custom_attribute: bool,
+) -> TokenStream {
+    let mut item = syn::parse_macro_input!(item as Item);
+
+    let metadata = match Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated
+        .parse(attr)
+        .and_then(metadata)
+    {
+        Ok(i) => i,
+        Err(e) => return e.to_compile_error().into(),
     };
 
-    let callpath = simplify_path(path);
+    // other annotation attributes
+    let mut other_annotations = Vec::new();
+    // any other attribute present
+    let mut other_attrs = Vec::new();
+    for i in item.attrs() {
+        if let Some(lst) = i.path.segments.last() {
+            if &*lst.ident.to_string() == "label" {
+                let metadata = match attribute_metadata(&i) {
+                    Ok(i) => i,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+                let path = match simplify_path(i.path) {
+                    Ok(i) => i,
+                    Err(e) => return e.to_compile_error().into(),
+                };
+                other_annotations.push((path, metadata));
+                continue;
+            }
+        }
+        other_attrs.push(i);
+    }
+
+    let doc = doc_string(&other_attrs);
+    // The compiler removes `cfg` attributes before expanding the label attribute, but when
+    // an item still has them the item is only registered when it exists.
+    // Lints allowed on the item, like `deprecated` for a deprecated label, are allowed where it's registered too.
+    let cfgs: Vec<_> = other_attrs
+        .iter()
+        .filter(|i| i.path.is_ident("cfg") || i.path.is_ident("allow"))
+        .cloned()
+        .collect();
+
+    // remove all label from the function's attributes
+    // but keep other attributes
+    item.set_attrs(other_attrs);
+
+    let item_name = item.name();
+
+    let callpath = match callpath {
+        Some(i) => i,
+        None => match label_path(item_name, &other_annotations, custom_attribute) {
+            Ok(i) => i,
+            Err(e) => return e.to_compile_error().into(),
+        },
+    };
+    let mut paths = vec![(callpath, metadata)];
+    paths.extend(other_annotations);
     let item_name_str = format!("{}", item_name);
+    let location = quote_spanned! {item_name.span()=>
+        label::Location::__new(file!(), line!(), column!())
+    };
 
     let item_quote = match &item {
         Item::Func(_) => quote! {
             #item_name
         },
+        Item::Static(i) if i.mutability.is_some() => quote! {
+            std::ptr::addr_of_mut!(#item_name)
+        },
         Item::Static(_) => {
             quote! {
                 &#item_name
             }
         }
-        Item::Const(_) => quote! {
+        Item::Const(_) | Item::Struct(_) => quote! {
             &#item_name
         },
+        Item::Impl(_, path) => quote! {
+            &#path
+        },
+    };
+
+    // `static mut` items are registered with a pointer, so mutable labels can lock them.
+    let add = match &item {
+        Item::Static(i) if i.mutability.is_some() => quote! { __add_label_mut },
+        _ => quote! { __add_label },
+    };
+
+    // Functions are checked against the signature of every label by its `add::__coerce`, which
+    // reports a mismatch with the names of the function and the label. The names are given
+    // as type parameters of a function doing the check, which are shown by their name in errors.
+    // Any other type is given through an alias, so the parameters don't shadow it.
+    let pointer = match &item {
+        Item::Func(i) => fn_pointer(&i.sig).map(|pointer| (pointer, i.sig.span())),
+        _ => None,
+    };
+    // The visibility of every item is checked against the visibilities allowed by every label
+    // in the same way, with the error at the visibility of the item.
+    let vis = item.vis();
+    let visibility = visibility_tokens(&vis);
+    let vis_span = match &vis {
+        syn::Visibility::Inherited => item_name.span(),
+        vis => vis.span(),
     };
+    let kind = syn::Ident::new(visibility_kind(&vis), vis_span);
+    let mut vis_item_name = item_name.clone();
+    vis_item_name.set_span(vis_span);
+
+    let mut checks = Vec::new();
+    let mut calls = Vec::new();
+    for (index, (path, metadata)) in paths.iter().enumerate() {
+        // the name of a custom attribute used directly, like `#[handler]`, names the label as well.
+        let label_name = path
+            .segments
+            .iter()
+            .rev()
+            .nth(if path.segments.len() == 1 { 0 } else { 1 })
+            .map(|i| &i.ident)
+            .filter(|i| !matches!(&*i.to_string(), "self" | "super" | "crate"));
+
+        let ident = |name: &str| syn::Ident::new(&format!("{}{}", name, index), vis_span);
+        let check = ident("__label_visibility_");
+        let visible = ident("__label_visible_");
+        let visibility_kind = ident("__LabelVisibility");
+        let label_name_or_label = label_name
+            .cloned()
+            .unwrap_or_else(|| syn::Ident::new("label", vis_span));
+        let names = if label_name_or_label == *item_name {
+            vec![&vis_item_name]
+        } else {
+            vec![&vis_item_name, &label_name_or_label]
+        };
+        // the checks don't warn about deprecated labels, which is done once where the item is registered.
+        checks.push(quote_spanned! {vis_span=>
+            #[allow(deprecated)]
+            use #path::__visible as #visible;
+            use label::__visibility::#kind as #visibility_kind;
+
+            #[allow(non_camel_case_types, dead_code, deprecated)]
+            fn #check<#(#names),*>() {
+                #visible::<#vis_item_name, #label_name_or_label, #visibility_kind>();
+            }
+        });
+
+        // `static mut` items can only be labeled with mutable labels, with the error at `mut`.
+        if let Item::Static(syn::ItemStatic {
+            mutability: Some(mutability),
+            ..
+        }) = &item
+        {
+            let span = mutability.span;
+            let ident = |name: &str| syn::Ident::new(&format!("{}{}", name, index), span);
+            let check = ident("__label_mutable_");
+            let mutable = ident("__label_mutable_item_");
+            let mut item_name = item_name.clone();
+            item_name.set_span(span);
+            let mut label_name = label_name_or_label.clone();
+            label_name.set_span(span);
+            let names = if label_name == item_name {
+                vec![&item_name]
+            } else {
+                vec![&item_name, &label_name]
+            };
+            checks.push(quote_spanned! {span=>
+                #[allow(deprecated)]
+                use #path::__mutable as #mutable;
+
+                #[allow(non_camel_case_types, dead_code, deprecated)]
+                fn #check<#(#names),*>() {
+                    #mutable::<#item_name, #label_name>();
+                }
+            });
+        }
+
+        // arguments which aren't integers are checked against the integer arguments declared by
+        // the label, like the visibility with the error at the value.
+        for (argument, (key, value, span)) in metadata.iter().enumerate() {
+            let key_name = match syn::parse_str::<syn::Ident>(key) {
+                Ok(mut i) => {
+                    i.set_span(*span);
+                    i
+                }
+                Err(_) => continue,
+            };
+            let mut item_name = item_name.clone();
+            item_name.set_span(*span);
+            let mut label_name = label_name_or_label.clone();
+            label_name.set_span(*span);
+            let mut names = vec![&item_name];
+            for i in [&label_name, &key_name] {
+                if !names.contains(&i) {
+                    names.push(i);
+                }
+            }
+
+            let kinds = [
+                ("integer", value.parse::<i64>().is_err()),
+                ("unsigned", value.parse::<u64>().is_err()),
+            ];
+            for (kind, _) in kinds.iter().filter(|(_, invalid)| *invalid) {
+                let ident = |name: &str| {
+                    let name = format!("{}{}_{}_{}", name, kind, index, argument);
+                    syn::Ident::new(&name, *span)
+                };
+                let check = ident("__label_check_");
+                let function = ident("__label_");
+                let declared = syn::Ident::new(
+                    &ident("__label_declared_").to_string().to_uppercase(),
+                    *span,
+                );
+                let check_function = syn::Ident::new(&format!("__{}", kind), *span);
+                let arguments =
+                    syn::Ident::new(&format!("__{}_ARGUMENTS", kind.to_uppercase()), *span);
+                checks.push(quote_spanned! {*span=>
+                    use label::#check_function as #function;
+                    #[allow(deprecated)]
+                    const #declared: bool = label::__declares(#path::#arguments, #key);
+
+                    #[allow(non_camel_case_types, dead_code)]
+                    fn #check<#(#names),*>() {
+                        #function::<#item_name, #label_name, #key_name, { #declared }>();
+                    }
+                });
+            }
+        }
+
+        let item = match (&pointer, label_name) {
+            (Some((pointer, span)), Some(label_name)) => {
+                let ident = |name: &str| syn::Ident::new(&format!("{}{}", name, index), *span);
+                let check = ident("__label_signature_");
+                let coerce = ident("__label_coerce_");
+                let function = ident("__LabelFunction");
+                let expected = ident("__LabelExpected");
+                let names = if label_name == item_name {
+                    vec![item_name]
+                } else {
+                    vec![item_name, label_name]
+                };
+                let units = names.iter().map(|_| quote! { () });
+
+                checks.push(quote_spanned! {*span=>
+                    #[allow(deprecated)]
+                    use #path::__coerce as #coerce;
+                    type #function = #pointer;
+                    #[allow(deprecated)]
+                    type #expected = #path::__Signature;
+
+                    #[allow(non_camel_case_types, deprecated)]
+                    fn #check<#(#names),*>(item: #function) -> #expected {
+                        #coerce::<#item_name, #label_name, #function>(item)
+                    }
+                });
+                quote! { #check::<#(#units),*>(#item_name) }
+            }
+            _ => item_quote.clone(),
+        };
+
+        let metadata_tokens = metadata_tokens(metadata);
+        calls.push(quote! {
+            #path::#add(#item_name_str, #doc, #location, #visibility, #metadata_tokens, #item);
+        });
+    }
 
     let result = quote! {
         #item
 
+        #(#cfgs)*
         #[allow(non_snake_case)]
         // This uses: https://github.com/rust-lang/rust/issues/54912 to make anonymous modules.
         // Anonymous modules use the parent scope meaning no more imports of `super::*` are needed
         const _: () = {
             use label::ctor;
 
+            #(#checks)*
+
             #[ctor]
             fn create () {
                 // Safety: This is unsafe because sometimes I use mut statics here. However, I'm only giving out pointers
                 // to them for which I make sure you can't use them without an unsafe block where they are used.
                 unsafe {
                     // register for all label it should be registered for
-                    #callpath::__add_label(#item_name_str, #item_quote);
-
-                    #(#other_annotations ::__add_label(#item_name_str, #item_quote);)*
+                    #(#calls)*
                 }
             }
         };
@@ -214,34 +1007,152 @@ pub fn __label(_attr: TokenStream, item: TokenStream) -> TokenStream {
 }
 
 struct Definitions {
-    signatures: Punctuated<Definition, syn::Token![;]>,
+    signatures: Punctuated<(Options, syn::Visibility, Definition), syn::Token![;]>,
 }
 
 impl Parse for Definitions {
     fn parse(input: ParseStream) -> Result<Self> {
         Ok(Self {
-            signatures: input.parse_terminated::<_, syn::Token![;]>(Definition::parse)?,
+            signatures: input.parse_terminated::<_, syn::Token![;]>(|input| {
+                Ok((input.parse()?, input.parse()?, input.parse()?))
+            })?,
         })
     }
 }
 
-enum Definition {
-    Function {
-        name: syn::Ident,
-        params: syn::punctuated::Punctuated<syn::BareFnArg, syn::Token![,]>,
-        generics: syn::Generics,
-        returntype: syn::ReturnType,
-    },
-    Static {
-        name: syn::Ident,
-        var_type: syn::Type,
-    },
+/// The options of a label, given in `#[label(..)]` before its definition.
+#[derive(Default)]
+struct Options {
+    /// The other attributes before the definition, like doc comments, which are put on the module of the label.
+    attrs: Vec<syn::Attribute>,
+    /// The name of the attribute next to `label`, like `register` for `#[my_label::register]`.
+    attribute: Option<syn::Ident>,
+    /// The kinds of visibility labeled items can have, see `visibility_kind`. Empty for any visibility.
+    required: Vec<&'static str>,
+    /// The kinds of visibility labeled items can't have.
+    forbidden: Vec<&'static str>,
+    /// The arguments of labeled items which have to be integers.
+    integers: Vec<syn::Ident>,
+    /// The arguments of labeled items which have to be non-negative integers.
+    unsigned: Vec<syn::Ident>,
 }
 
-impl Parse for Definition {
+impl Options {
+    /// The kinds of visibility labeled items can have.
+    fn allowed(&self) -> Vec<&'static str> {
+        ["Public", "Crate", "Super", "Restricted", "Private"]
+            .iter()
+            .copied()
+            .filter(|i| self.required.is_empty() || self.required.contains(i))
+            .filter(|i| !self.forbidden.contains(i))
+            .collect()
+    }
+}
+
+/// The visibility given to the `require` and `forbid` options of a label.
+fn option_visibility(input: ParseStream) -> Result<&'static str> {
+    let error = |span| {
+        syn::Error::new(
+            span,
+            "expected `pub`, `pub(crate)`, `pub(super)` or `private` as visibility",
+        )
+    };
+
+    match input.parse()? {
+        syn::Visibility::Inherited => {
+            let name: syn::Ident = input.parse()?;
+            if name == "private" {
+                Ok("Private")
+            } else {
+                Err(error(name.span()))
+            }
+        }
+        syn::Visibility::Restricted(i) if i.in_token.is_some() => Err(error(i.span())),
+        vis => Ok(visibility_kind(&vis)),
+    }
+}
+
+impl Parse for Options {
     fn parse(input: ParseStream) -> Result<Self> {
-        let _ = input.parse::<syn::Visibility>();
+        let mut options = Options::default();
+
+        for attr in input.call(syn::Attribute::parse_outer)? {
+            if !attr.path.is_ident("label") {
+                options.attrs.push(attr);
+                continue;
+            }
+
+            attr.parse_args_with(|input: ParseStream| {
+                while !input.is_empty() {
+                    let key: syn::Ident = input.parse()?;
+                    input.parse::<syn::Token![=]>()?;
+
+                    match &*key.to_string() {
+                        "attribute" => {
+                            let name: syn::Ident = input.parse()?;
+                            if name == "label" || name == "add" {
+                                return Err(syn::Error::new(
+                                    name.span(),
+                                    format!("the attribute of a label can't be named `{}`", name),
+                                ));
+                            }
+                            options.attribute = Some(name);
+                        }
+                        "require" => options.required.push(input.call(option_visibility)?),
+                        "forbid" => options.forbidden.push(input.call(option_visibility)?),
+                        "integer" => options.integers.push(input.parse()?),
+                        "unsigned" => options.unsigned.push(input.parse()?),
+                        _ => {
+                            return Err(syn::Error::new(
+                                key.span(),
+                                format!(
+                                "unknown option `{}`, expected `attribute`, `require`, `forbid`, `integer` or `unsigned`",
+                                key
+                            ),
+                            ))
+                        }
+                    }
+
+                    if options.allowed().is_empty() {
+                        return Err(syn::Error::new(
+                            key.span(),
+                            "the options of this label don't allow items with any visibility",
+                        ));
+                    }
 
+                    if !input.is_empty() {
+                        input.parse::<syn::Token![,]>()?;
+                    }
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(options)
+    }
+}
+
+enum Definition {
+    Function {
+        name: syn::Ident,
+        params: syn::punctuated::Punctuated<syn::BareFnArg, syn::Token![,]>,
+        generics: syn::Generics,
+        returntype: syn::ReturnType,
+    },
+    Static {
+        name: syn::Ident,
+        var_type: syn::Type,
+        mutable: bool,
+    },
+    Event {
+        name: syn::Ident,
+        event_type: syn::Type,
+        returntype: syn::ReturnType,
+    },
+}
+
+impl Parse for Definition {
+    fn parse(input: ParseStream) -> Result<Self> {
         if input.peek(syn::Token![fn]) {
             input.parse::<syn::Token![fn]>()?;
 
@@ -277,14 +1188,18 @@ impl Parse for Definition {
         } else if input.peek(syn::Token![static]) {
             input.parse::<syn::Token![static]>()?;
 
-            let _ = input.parse::<syn::Token![mut]>();
+            let mutable = input.parse::<Option<syn::Token![mut]>>()?.is_some();
             let name = input.parse::<syn::Ident>()?;
 
             input.parse::<syn::Token![:]>()?;
 
             let var_type: syn::Type = input.parse()?;
 
-            Ok(Definition::Static { name, var_type })
+            Ok(Definition::Static {
+                name,
+                var_type,
+                mutable,
+            })
         } else if input.peek(syn::Token![const]) {
             input.parse::<syn::Token![const]>()?;
 
@@ -294,23 +1209,633 @@ impl Parse for Definition {
 
             let var_type: syn::Type = input.parse()?;
 
-            Ok(Definition::Static { name, var_type })
+            Ok(Definition::Static {
+                name,
+                var_type,
+                mutable: false,
+            })
+        } else if input
+            .fork()
+            .parse::<syn::Ident>()
+            .is_ok_and(|i| i == "event")
+        {
+            input.parse::<syn::Ident>()?;
+
+            let name = input.parse::<syn::Ident>()?;
+
+            let content;
+            syn::parenthesized!(
+               content in input
+            );
+            if content.is_empty() {
+                return Err(content.error(
+                    "expected the type of the event between parentheses, like `event on_start(Started);`",
+                ));
+            }
+            let event_type: syn::Type = content.parse()?;
+
+            let returntype = input.parse::<syn::ReturnType>()?;
+
+            Ok(Definition::Event {
+                name,
+                event_type,
+                returntype,
+            })
         } else {
-            Err(input
-                .error("Expected either function definition, static variable or const variable."))
+            Err(input.error(
+                "expected a label definition starting with `fn`, `static`, `const` or `event`",
+            ))
+        }
+    }
+}
+
+/// The visibility of the functions in the module of a label declared with `vis`, which makes them
+/// visible in the same places as the label. Paths like `pub(super)` are relative to the module.
+fn inner_visibility(vis: &syn::Visibility) -> proc_macro2::TokenStream {
+    match vis {
+        syn::Visibility::Inherited => quote! { pub(super) },
+        syn::Visibility::Restricted(i) if i.path.is_ident("self") => quote! { pub(super) },
+        syn::Visibility::Restricted(i) => {
+            let mut segments = i.path.segments.iter().map(|i| &i.ident).peekable();
+            match segments.peek() {
+                Some(first) if *first == "self" => {
+                    segments.next();
+                    quote! { pub(in super #(::#segments)*) }
+                }
+                Some(first) if *first == "super" => quote! { pub(in super #(::#segments)*) },
+                _ => quote! { #vis },
+            }
+        }
+        vis => quote! { #vis },
+    }
+}
+
+/// Names the elided lifetimes (`&T` and `'_`) in a type, so the type can be used in a where clause.
+/// Nested function signatures have their own elision rules, and are left alone.
+struct NameElided {
+    // the name for every elided lifetime, or None to create a new lifetime for each of them.
+    name: Option<syn::Lifetime>,
+    created: Vec<syn::Lifetime>,
+}
+
+impl NameElided {
+    fn lifetime(&mut self) -> syn::Lifetime {
+        if let Some(i) = &self.name {
+            return i.clone();
+        }
+
+        let res = syn::Lifetime::new(
+            &format!("'__label{}", self.created.len()),
+            proc_macro2::Span::call_site(),
+        );
+        self.created.push(res.clone());
+        res
+    }
+}
+
+impl VisitMut for NameElided {
+    fn visit_type_reference_mut(&mut self, i: &mut syn::TypeReference) {
+        if i.lifetime.is_none() {
+            i.lifetime = Some(self.lifetime());
+        }
+        visit_mut::visit_type_reference_mut(self, i);
+    }
+
+    fn visit_lifetime_mut(&mut self, i: &mut syn::Lifetime) {
+        if i.ident == "_" {
+            *i = self.lifetime();
+        }
+    }
+
+    fn visit_type_bare_fn_mut(&mut self, _: &mut syn::TypeBareFn) {}
+
+    fn visit_parenthesized_generic_arguments_mut(
+        &mut self,
+        _: &mut syn::ParenthesizedGenericArguments,
+    ) {
+    }
+}
+
+/// The named lifetimes used in a type, except `'static`.
+#[derive(Default)]
+struct Lifetimes(Vec<syn::Lifetime>);
+
+impl<'ast> Visit<'ast> for Lifetimes {
+    fn visit_lifetime(&mut self, i: &syn::Lifetime) {
+        if i.ident != "static" && i.ident != "_" && !self.0.contains(i) {
+            self.0.push(i.clone());
+        }
+    }
+
+    fn visit_type_bare_fn(&mut self, _: &syn::TypeBareFn) {}
+}
+
+/// Finds lifetimes which are not bound by the signature of a label, like `'static`.
+struct ConcreteLifetimes<'a> {
+    bound: Vec<&'a syn::Lifetime>,
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for ConcreteLifetimes<'_> {
+    fn visit_lifetime(&mut self, i: &'ast syn::Lifetime) {
+        if i.ident != "_" && !self.bound.contains(&i) {
+            self.found = true;
+        }
+    }
+}
+
+/// The name of a type without generic arguments, like `Result` for `io::Result<()>`.
+fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(i) => i.path.segments.last().map(|i| i.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// The `call_all` functions of a function label. See `label::calls`.
+fn call_functions(
+    vis: &proc_macro2::TokenStream,
+    generics: &syn::Generics,
+    params: &Punctuated<syn::BareFnArg, syn::Token![,]>,
+    returntype: &syn::ReturnType,
+) -> proc_macro2::TokenStream {
+    let mut inputs = NameElided {
+        name: None,
+        created: Vec::new(),
+    };
+    let mut names: Vec<syn::Ident> = Vec::new();
+    let mut types = Vec::new();
+    let mut args = Vec::new();
+    let mut bounds = Vec::new();
+    let mut borrowed_mutably = Lifetimes::default();
+
+    for (index, param) in params.iter().enumerate() {
+        let name = match &param.name {
+            Some((i, _)) if i != "_" && !names.contains(i) => i.clone(),
+            _ => quote::format_ident!("arg{}", index),
+        };
+
+        let mut ty = param.ty.clone();
+        inputs.visit_type_mut(&mut ty);
+
+        // every function gets its own copy of the arguments.
+        args.push(match &ty {
+            syn::Type::Reference(i) if i.mutability.is_some() => {
+                // the lifetime was named above.
+                if let Some(lifetime) = &i.lifetime {
+                    borrowed_mutably.visit_lifetime(lifetime);
+                }
+                quote! { &mut *#name }
+            }
+            syn::Type::Reference(_) => quote! { #name },
+            _ => {
+                // the `for` makes sure the bound is checked when calling the function,
+                // so labels with arguments which can't be cloned still compile.
+                bounds.push(quote! { for<'__label> #ty: std::clone::Clone });
+                quote! { #name.clone() }
+            }
+        });
+        names.push(name);
+        types.push(ty);
+    }
+
+    let mut output = match returntype {
+        syn::ReturnType::Default => syn::parse_quote! { () },
+        syn::ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+
+    // like the elision rules: with a single lifetime in the arguments, it is the lifetime of the return value.
+    let mut input_lifetimes = Lifetimes::default();
+    for ty in &types {
+        input_lifetimes.visit_type(ty);
+    }
+    if let [lifetime] = &input_lifetimes.0[..] {
+        NameElided {
+            name: Some(lifetime.clone()),
+            created: Vec::new(),
+        }
+        .visit_type_mut(&mut output);
+    }
+
+    // a `&mut` argument can't be reborrowed for every function when the return values borrow from it.
+    let mut output_lifetimes = Lifetimes::default();
+    output_lifetimes.visit_type(&output);
+    if output_lifetimes
+        .0
+        .iter()
+        .any(|i| borrowed_mutably.0.contains(i))
+    {
+        return quote! {};
+    }
+
+    let lifetimes: Vec<_> = generics
+        .lifetimes()
+        .map(|i| quote! { #i })
+        .chain(inputs.created.iter().map(|i| quote! { #i }))
+        .collect();
+    let call = quote! {
+        |__label_function| __label_function(#(#args),*)
+    };
+
+    let mut res = quote! {
+        #vis fn call_all<#(#lifetimes),*>(#(#names: #types),*) -> std::vec::Vec<(&'static str, #output)>
+        where #(#bounds,)*
+        {
+            label::calls::__call_all(iter_entries(), #call)
+        }
+
+        #vis fn call_all_isolated<#(#lifetimes),*>(#(#names: #types),*)
+            -> std::vec::Vec<label::calls::Outcome<#output>>
+        where #(#bounds,)* for<'__label> (): label::calls::__Unwinding
+        {
+            label::calls::__call_all_isolated(iter_entries(), #call)
+        }
+    };
+
+    match type_name(&output).as_deref() {
+        Some("Result") => res.extend(quote! {
+            #vis fn try_call_all<#(#lifetimes),*>(#(#names: #types),*)
+                -> std::result::Result<
+                    std::vec::Vec<(&'static str, <#output as label::calls::Fallible>::Value)>,
+                    (&'static str, <#output as label::calls::Fallible>::Error)
+                >
+            where #(#bounds,)* for<'__label> #output: label::calls::Fallible
+            {
+                label::calls::__try_call_all(iter_entries(), #call)
+            }
+
+            #vis fn call_all_collect_errors<#(#lifetimes),*>(#(#names: #types),*)
+                -> std::result::Result<
+                    std::vec::Vec<(&'static str, <#output as label::calls::Fallible>::Value)>,
+                    std::vec::Vec<(&'static str, <#output as label::calls::Fallible>::Error)>
+                >
+            where #(#bounds,)* for<'__label> #output: label::calls::Fallible
+            {
+                label::calls::__call_all_collect_errors(iter_entries(), #call)
+            }
+        }),
+        Some("Option") => res.extend(quote! {
+            #vis fn call_first_some<#(#lifetimes),*>(#(#names: #types),*)
+                -> std::option::Option<(&'static str, <#output as label::calls::Optional>::Value)>
+            where #(#bounds,)* for<'__label> #output: label::calls::Optional
+            {
+                label::calls::__call_first_some(iter_entries(), #call)
+            }
+        }),
+        _ => {}
+    }
+
+    res
+}
+
+/// The module of a label, with the functions to go through the items labeled with it.
+fn label_module(
+    options: &Options,
+    vis: &syn::Visibility,
+    definition: &Definition,
+) -> proc_macro2::TokenStream {
+    let inner_vis = inner_visibility(vis);
+
+    // Labels with lifetimes like `'static` in their signature don't check the exact signature
+    // of labeled functions, since more general functions can be labeled with them as well.
+    let checked = match definition {
+        Definition::Function {
+            generics,
+            params,
+            returntype,
+            ..
+        } => {
+            let mut concrete = ConcreteLifetimes {
+                bound: generics.lifetimes().map(|i| &i.lifetime).collect(),
+                found: false,
+            };
+            params.iter().for_each(|i| concrete.visit_bare_fn_arg(i));
+            concrete.visit_return_type(returntype);
+            !concrete.found
+        }
+        Definition::Static { .. } => true,
+        Definition::Event {
+            event_type,
+            returntype,
+            ..
+        } => {
+            let mut concrete = ConcreteLifetimes {
+                bound: Vec::new(),
+                found: false,
+            };
+            concrete.visit_type(event_type);
+            concrete.visit_return_type(returntype);
+            !concrete.found
+        }
+    };
+
+    // labels which can't contain `static mut` items have an `add_mut` which is never called,
+    // so labeling one only fails the `__mutable` check.
+    let no_add_mut = quote! {
+        /// # Safety
+        ///
+        /// Never called, as labeling a `static mut` with this label fails the `__mutable` check.
+        pub unsafe fn __add_label_mut<T>(
+            _name: &'static str,
+            _doc: &'static str,
+            _location: label::Location,
+            _visibility: label::Visibility,
+            _metadata: &'static [(&'static str, &'static str)],
+            _item: *mut T,
+        ) {
+            unreachable!("a `static mut` can only be labeled with a mutable label")
+        }
+    };
+
+    // `add_mut` registers `static mut` items, for labels which can contain them.
+    let (signature, name, extra, add_mut) = match definition {
+        Definition::Function {
+            name,
+            generics,
+            params,
+            returntype,
+        } => {
+            let lifetimes = generics.lifetimes();
+            let signature = quote! {
+                for <#(#lifetimes),*> fn(#params) #returntype
+            };
+            let extra = call_functions(&inner_vis, generics, params, returntype);
+
+            (signature, name, extra, no_add_mut)
+        }
+        Definition::Static {
+            name,
+            var_type,
+            mutable: false,
+        } => (
+            quote! {
+                &'static #var_type
+            },
+            name,
+            quote! {},
+            no_add_mut,
+        ),
+        Definition::Static {
+            name,
+            var_type,
+            mutable: true,
+        } => (
+            quote! {
+                label::mutable::StaticMut<#var_type>
+            },
+            name,
+            quote! {
+                #inner_vis fn iter_mut() -> impl Iterator<Item = label::mutable::WriteGuard<#var_type>> {
+                    iter().map(|i| i.write())
+                }
+            },
+            quote! {
+                /// # Safety
+                ///
+                /// `item` must point to a `static mut`.
+                pub unsafe fn __add_label_mut(
+                    name: &'static str,
+                    doc: &'static str,
+                    location: label::Location,
+                    visibility: label::Visibility,
+                    metadata: &'static [(&'static str, &'static str)],
+                    item: *mut #var_type,
+                ) {
+                    __add_label(name, doc, location, visibility, metadata, unsafe { label::mutable::StaticMut::__new(item) })
+                }
+
+                impl<Item, Label> label::mutable::__Mutable<Item, Label> for __Mutability {}
+            },
+        ),
+        Definition::Event {
+            name,
+            event_type,
+            returntype,
+        } => {
+            let subscriber = match returntype {
+                syn::ReturnType::Default => quote! { () },
+                syn::ReturnType::Type(_, ty) => quote! { #ty },
+            };
+
+            (
+                quote! {
+                    fn(&#event_type) #returntype
+                },
+                name,
+                quote! {
+                    #inner_vis fn emit(event: &#event_type) -> label::events::Emitted<<#subscriber as label::events::Subscriber>::Error> {
+                        label::events::__emit(iter_entries(), event, false)
+                    }
+
+                    #inner_vis fn emit_collect(event: &#event_type) -> label::events::Emitted<<#subscriber as label::events::Subscriber>::Error> {
+                        label::events::__emit(iter_entries(), event, true)
+                    }
+                },
+                no_add_mut,
+            )
+        }
+    };
+
+    let coerce = if checked {
+        quote! {
+            #[doc(hidden)]
+            // Used where functions are labeled, see `label::__Signature`.
+            pub fn __coerce<Function, Label, T: label::__Signature<Function, Label, #signature>>(
+                item: T,
+            ) -> #signature {
+                label::__Signature::__coerce(item)
+            }
+        }
+    } else {
+        quote! {
+            #[doc(hidden)]
+            // Used where functions are labeled, without checking the exact signature.
+            pub fn __coerce<Function, Label, T>(item: #signature) -> #signature {
+                item
+            }
+        }
+    };
+
+    // only generated when label is compiled with the `rayon` feature,
+    // the `cfg` can't be emitted as the user's crate doesn't have that feature.
+    let parallel = if cfg!(feature = "rayon") {
+        quote! {
+            #inner_vis fn par_iter() -> impl label::rayon::iter::IndexedParallelIterator<Item = #signature> {
+                use label::rayon::prelude::*;
+
+                // Safety: after FUNCTIONS is populated (before main is called),
+                // FUNCTIONS remains unchanged for the entire rest of the program.
+                unsafe {
+                    FUNCTIONS.as_deref().unwrap_or(&[]).par_iter().map(|i| i.item())
+                }
+            }
+
+            #inner_vis fn par_iter_named() -> impl label::rayon::iter::IndexedParallelIterator<Item = (&'static str, #signature)> {
+                use label::rayon::prelude::*;
+
+                // Safety: after FUNCTIONS is populated (before main is called),
+                // FUNCTIONS remains unchanged for the entire rest of the program.
+                unsafe {
+                    FUNCTIONS.as_deref().unwrap_or(&[]).par_iter().map(|i| (i.name(), i.item()))
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // An attribute with another name is also the `add` module under that name, so
+    // `#[my_label::register]` finds its label without a `label` suffix to replace. It's expanded
+    // by `__label_attribute`, so the path of any other attribute isn't taken for an `add` module.
+    let attribute = options.attribute.as_ref().map(|i| {
+        quote! {
+            pub use label::__label_attribute as #i;
+            #[doc(hidden)]
+            pub use self::add as #i;
+        }
+    });
+
+    let allowed = options
+        .allowed()
+        .into_iter()
+        .map(|i| syn::Ident::new(i, proc_macro2::Span::call_site()));
+
+    // event labels sort their subscribers by `priority`, so it has to be an integer.
+    let priority = match definition {
+        Definition::Event { .. } => Some("priority".to_string()),
+        _ => None,
+    };
+    let integers = options
+        .integers
+        .iter()
+        .map(|i| i.to_string())
+        .chain(priority);
+    let unsigned = options.unsigned.iter().map(|i| i.to_string());
+
+    let attrs = &options.attrs;
+
+    quote! {
+        #(#attrs)*
+        #[allow(non_snake_case)]
+        #vis mod #name {
+            use super::*;
+
+            pub use std::collections::HashMap;
+            pub use label::__label as label;
+            #attribute
+
+            pub static mut FUNCTIONS: Option<Vec<label::Entry<#signature>>> = None;
+
+            #inner_vis fn iter() -> impl Iterator<Item = #signature> {
+                // Safety: after FUNCTIONS is populated (before main is called),
+                // FUNCTIONS remains unchanged for the entire rest of the program.
+
+                unsafe{
+                    FUNCTIONS.iter().flat_map(|i| i.iter().map(|i| i.item()))
+                }
+            }
+
+            #inner_vis fn iter_named() -> impl Iterator<Item = (&'static str, #signature)> {
+                // Safety: after FUNCTIONS is populated (before main is called),
+                // FUNCTIONS remains unchanged for the entire rest of the program.
+                unsafe{
+                    FUNCTIONS.iter().flat_map(|i| i.iter().map(|i| (i.name(), i.item())))
+                }
+            }
+
+            #inner_vis fn iter_entries() -> impl Iterator<Item = label::Entry<#signature>> {
+                // Safety: after FUNCTIONS is populated (before main is called),
+                // FUNCTIONS remains unchanged for the entire rest of the program.
+                unsafe{
+                    FUNCTIONS.iter().flat_map(|i| i).copied()
+                }
+            }
+
+            #parallel
+
+            #extra
+
+            pub mod add {
+                use super::*;
+
+                #[doc(hidden)]
+                // The type of labeled items, which labeled functions are checked against.
+                pub type __Signature = #signature;
+
+                // WARNING: DO NOT CALL. THIS HAS TO BE PUBLIC FOR OTHER
+                // PARTS OF THE LIBRARY TO WORK BUT SHOULD NEVER BE USED.
+                pub fn __add_label(
+                    name: &'static str,
+                    doc: &'static str,
+                    location: label::Location,
+                    visibility: label::Visibility,
+                    metadata: &'static [(&'static str, &'static str)],
+                    func: #signature,
+                ) {
+                    let entry = label::Entry::__new(name, doc, location, visibility, metadata, func);
+                    unsafe {
+                        if let Some(f) = &mut FUNCTIONS {
+                            f.push(entry);
+                        } else {
+                            FUNCTIONS = Some(vec![entry])
+                        }
+                    }
+                }
+
+                #add_mut
+
+                #coerce
+
+                #[doc(hidden)]
+                // The visibilities labeled items can have, see `label::__Visible`.
+                pub struct __Visibility;
+
+                #(impl<Item, Label> label::__Visible<Item, Label, label::__visibility::#allowed> for __Visibility {})*
+
+                #[doc(hidden)]
+                // Used where items are labeled.
+                pub fn __visible<Item, Label, Visibility>()
+                where
+                    __Visibility: label::__Visible<Item, Label, Visibility>,
+                {
+                }
+
+                #[doc(hidden)]
+                // Whether `static mut` items can be labeled, see `label::mutable::__Mutable`.
+                pub struct __Mutability;
+
+                #[doc(hidden)]
+                // Used where `static mut` items are labeled.
+                pub fn __mutable<Item, Label>()
+                where
+                    __Mutability: label::mutable::__Mutable<Item, Label>,
+                {
+                }
+
+                #[doc(hidden)]
+                // The arguments which have to be integers, see `label::__Integer`.
+                pub const __INTEGER_ARGUMENTS: &[&str] = &[#(#integers),*];
+
+                #[doc(hidden)]
+                // The arguments which have to be non-negative integers, see `label::__Unsigned`.
+                pub const __UNSIGNED_ARGUMENTS: &[&str] = &[#(#unsigned),*];
+            }
         }
     }
 }
 
 #[proc_macro]
 /// Creates a new label.
-/// ```
+///
+/// The examples here can't be compiled in this crate, the documentation of `label` has examples which are.
+///
+/// ```ignore
 /// create_label!(fn test() -> ());
 /// ```
 ///
 /// To use a label, add an attribute to a function in the following style:
 ///
-/// ```
+/// ```ignore
 /// #[test::label]
 /// fn my_function() {
 ///     // contents
@@ -319,11 +1844,74 @@ impl Parse for Definition {
 /// ```
 ///
 /// `test` is the name of your label (this has to be a full path to it. Labels can be imported).
+/// Like other items, labels are private to the module they are created in. A visibility like
+/// `pub fn test() -> ();` or `pub(crate) fn test() -> ();` applies to the label and its functions,
+/// which also makes it possible to label items in other modules or crates with it.
+/// Labels can also be renamed and re-exported with `use`, like `use my_crate::handlers as routes;`
+/// and `#[routes::label]`, and every item is registered once no matter which path is used.
+/// Only the attribute itself can't be imported: `use test::label;` and `#[label]` doesn't compile.
 /// The annotation has to end with `::label`, or otherwise it will not compile.
 ///
+/// To use another name for the attribute, give the label the `attribute` option. The attribute
+/// can then be used with that name as well, and it can be imported or renamed like any other item:
 ///
-/// It is possible to create multipe labels in one invocation of the `create_label!()` macro. The syntax for this is as follows:
+/// ```ignore
+/// create_label!(
+///     #[label(attribute = register)]
+///     fn handlers() -> ();
+/// );
+///
+/// use handlers::register as handler;
+///
+/// #[handlers::register]
+/// fn on_start() {}
+///
+/// #[handler]
+/// fn on_stop() {}
 /// ```
+///
+/// In the invocation of a macro generating the item, the attribute still has to end with `::label`.
+///
+/// The `require` and `forbid` options restrict the visibility of labeled items. Labeling an item
+/// with a visibility which isn't allowed is a compile error. The visibility is one of `pub`,
+/// `pub(crate)`, `pub(super)` or `private`, and both options can be given more than once:
+///
+/// ```ignore
+/// create_label!(
+///     #[label(require = pub)]
+///     fn plugins() -> ();
+///     #[label(forbid = pub, forbid = pub(crate))]
+///     fn internal_hooks() -> ();
+/// );
+/// ```
+///
+/// The visibility of every labeled item is also available from `label::Entry::visibility()`.
+///
+/// The `integer` and `unsigned` options name arguments of labeled items which have to be integers,
+/// or non-negative integers. Any other value for them is a compile error at the value. Event labels
+/// declare their `priority` argument as `integer`:
+///
+/// ```ignore
+/// create_label!(
+///     #[label(unsigned = retries)]
+///     fn jobs() -> ();
+/// );
+///
+/// #[jobs::label(retries = 3)]
+/// fn cleanup() {}
+/// ```
+///
+/// A function with a different signature than its label is a compile error as well, which names
+/// the function, its signature and the signature the label expects.
+///
+/// Items removed by `#[cfg]`, and items with a label attribute in a `cfg_attr` which doesn't apply,
+/// are not registered. The attribute can also be used on items generated by macros, when the path
+/// to the label is written in the macro or its invocation. When it isn't, like when a macro
+/// gets only the name of the label, use `#[label::labeled(path::to::test)]` instead.
+///
+///
+/// It is possible to create multipe labels in one invocation of the `create_label!()` macro. The syntax for this is as follows:
+/// ```ignore
 /// create_label!(
 ///     fn test() -> ();
 ///     fn test1(usize) -> (usize);
@@ -332,12 +1920,25 @@ impl Parse for Definition {
 ///
 /// ```
 ///
+/// Doc comments and other attributes on a definition, like `#[cfg]` or `#[deprecated]`, are put on
+/// the module of the label. Labeling an item with a deprecated label warns where it is labeled.
+///
+/// ```ignore
+/// create_label!(
+///     /// Handlers which are called for every request.
+///     pub fn handlers(&Request) -> Response;
+///
+///     #[deprecated(note = "use `handlers` instead")]
+///     pub fn old_handlers(&Request) -> Response;
+/// );
+/// ```
+///
 /// It is not supported to have two labels in scope with the same name, just like two structs in the same scope with the same name won't work either.
 ///
 ///1
 /// After a label is created, it is possible to iterate over all functions annotated with this label, using the iter function:
 ///
-/// ```
+/// ```ignore
 /// for func in test::iter() {
 ///     // do something with the function
 ///     func();
@@ -349,7 +1950,7 @@ impl Parse for Definition {
 ///
 /// Alternatively, you can iterate over functions and their names using the `iter_named()` function:
 ///
-/// ```
+/// ```ignore
 /// for (name, func) in test::iter_named() {
 ///     println!("name: {}", name);
 ///
@@ -359,100 +1960,138 @@ impl Parse for Definition {
 ///
 /// ```
 ///
+/// When the `rayon` feature of label is enabled, `par_iter()` and `par_iter_named()` are also available.
+/// These return an indexed parallel iterator, so collecting them returns the items
+/// in the same order as `iter()` and `iter_named()` would:
+///
+/// ```ignore
+/// use label::rayon::prelude::*;
+///
+/// let results: Vec<_> = test::par_iter_named()
+///     .map(|(name, func)| (name, func()))
+///     .collect();
+/// ```
+///
+/// To also get the doc comments of labeled items, use `iter_entries()`. This returns an
+/// `label::Entry` for every item, with its name, doc comment and the item itself:
+///
+/// ```ignore
+/// /// Prints a greeting.
+/// #[test::label]
+/// fn greet() {
+///     println!("Hello!");
+/// }
+///
+/// for entry in test::iter_entries() {
+///     println!("{}: {}", entry.name(), entry.doc());
+///
+///     // do something with the function
+///     (entry.item())();
+/// }
+/// ```
+///
+/// Labels can be given arguments, which are available as metadata on the entries.
+/// Arguments are either `key = literal` pairs, or just a `key`:
+///
+/// ```ignore
+/// #[test::label(ignore, timeout = 10, reason = "slow")]
+/// fn slow_function() {}
+///
+/// for entry in test::iter_entries() {
+///     if entry.meta("ignore").is_some() {
+///         continue;
+///     }
+///     println!("{:?}", entry.meta("timeout"));
+/// }
+/// ```
+///
+/// Function labels also have a `call_all()` function, which calls every labeled function with
+/// (a copy of) the same arguments. Labels returning a `Result` get `try_call_all()` and
+/// `call_all_collect_errors()`, and labels returning an `Option` get `call_first_some()`:
+///
+/// ```ignore
+/// create_label!(fn parse(&str) -> Result<u8, ParseIntError>);
+///
+/// for (name, result) in parse::call_all("42") {
+///     println!("{}: {:?}", name, result);
+/// }
+///
+/// let values = parse::try_call_all("42")?;
+/// ```
+///
+/// `call_all_isolated()` calls every function even when some of them panic, and returns
+/// a `label::calls::Outcome` with the return value or panic of every function.
+///
+/// See `label::calls` for more information.
+///
 /// Labels can also be given to `static` or `const` variables. Iterating over such labeled variables
 /// returns an `&'static` reference to the variable. You can define variable labels with
 /// `create_label!()`. It does not matter if you use `const` or `static`, they are handled the same.
-///  `static mut` is supported, though iterating over labels will *never* allow you to mutate these
-///  variables. `static mut` in `create_label!()` does nothing. If a `static mut` is locally updated,
-///  and the label is iterated over, the changed value is reflected.
 ///
+/// `static mut` variables can only be labeled with mutable labels, declared with `static mut` in `create_label!()`.
+/// Before 0.6, other labels could label them as well and gave out `&'static` references to them, which is now
+/// a compile error, as a mutable label could change the variable while such a reference exists. Iterating over a
+/// mutable label returns a `label::mutable::StaticMut` for every (`static mut`) variable, which can be locked
+/// for reading or writing. `iter_mut()` locks the variables for writing one after the other:
+///
+/// ```ignore
+/// create_label!(static mut counters: usize);
+///
+/// #[counters::label]
+/// static mut REQUESTS: usize = 0;
+///
+/// for mut counter in counters::iter_mut() {
+///     *counter += 1;
+/// }
 /// ```
+///
+/// See `label::mutable` for more information.
+///
+/// ```ignore
 /// create_label!(
 ///     const name: usize;
 ///     static other_name: usize;
 /// );
 /// ```
 ///
-/// ```
+/// ```ignore
 /// for i in name::iter() {
 ///     println!("value: {}", *i);
 /// }
 /// ```
 ///
+/// Labeled statics can be declared without a value, in which case their value is `<Type>::new()`.
+/// This is used by `label::config`, where values are loaded after the program starts:
+///
+/// ```ignore
+/// #[config::label(env = "PORT", default = 8080)]
+/// static PORT: ConfigValue<u16>;
+/// ```
+///
+/// Finally, event labels are function labels which take a reference to an event. Subscribers to the
+/// event are labeled with it, and `emit()` calls all of them. Subscribers return `()` by default,
+/// but can also return a `label::events::Propagation` to stop the event, or a `Result` of either.
+///
+/// ```ignore
+/// create_label!(
+///     event on_user_created(UserCreated);
+///     event on_user_deleted(UserDeleted) -> Result<(), String>;
+/// );
+///
+/// #[on_user_created::label(priority = 10)]
+/// fn send_welcome_mail(event: &UserCreated) {}
+///
+/// on_user_created::emit(&UserCreated { name });
+/// ```
+///
+/// See `label::events` for more information.
+///
 ///
 pub fn create_label(signatures: TokenStream) -> TokenStream {
     let labels = syn::parse_macro_input!(signatures as Definitions)
         .signatures
         .iter()
-        .map(|definition| {
-            let (signature, name) = match definition {
-                Definition::Function {
-                    name,
-                    generics,
-                    params,
-                    returntype,
-                } => {
-                    let lifetimes = generics.lifetimes();
-
-                    (
-                        quote! {
-                            for <#(#lifetimes),*> fn(#params) #returntype
-                        },
-                        name,
-                    )
-                }
-                Definition::Static { name, var_type } => (
-                    quote! {
-                        &'static #var_type
-                    },
-                    name,
-                ),
-            };
-
-            quote! {
-                #[allow(non_snake_case)]
-                pub mod #name {
-                    use super::*;
-
-                    pub use std::collections::HashMap;
-                    pub use label::__label as label;
-
-                    pub static mut FUNCTIONS: Option<Vec<(&'static str, #signature)>> = None;
-
-                    pub fn iter() -> impl Iterator<Item = #signature> {
-                        // Safety: after FUNCTIONS is populated (before main is called),
-                        // FUNCTIONS remains unchanged for the entire rest of the program.
-
-                        unsafe{
-                            FUNCTIONS.iter().flat_map(|i| i.iter().map(|i| &i.1)).cloned()
-                        }
-                    }
-
-                    pub fn iter_named() -> impl Iterator<Item = (&'static str, #signature)> {
-                        // Safety: after FUNCTIONS is populated (before main is called),
-                        // FUNCTIONS remains unchanged for the entire rest of the program.
-                        unsafe{
-                            FUNCTIONS.iter().flat_map(|i| i).cloned()
-                        }
-                    }
-
-                    pub mod add {
-                        use super::*;
-                        // WARNING: DO NOT CALL. THIS HAS TO BE PUBLIC FOR OTHER
-                        // PARTS OF THE LIBRARY TO WORK BUT SHOULD NEVER BE USED.
-                        pub fn __add_label(name: &'static str, func: #signature) {
-                            unsafe {
-                                if let Some(f) = &mut FUNCTIONS {
-                                    f.push((name, func));
-                                } else {
-                                    FUNCTIONS = Some(vec![(name, func)])
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        })
+        .map(|(options, vis, definition)| label_module(options, vis, definition))
         .collect::<Vec<_>>();
 
     let result = quote! {
@@ -0,0 +1,20 @@
+use label::bench::Bencher;
+use label::create_label;
+
+create_label!(
+    fn bench(&mut Bencher);
+);
+
+#[bench::label]
+fn parse_number(b: &mut Bencher) {
+    b.iter(|| "12345".parse::<usize>());
+}
+
+#[bench::label]
+fn iterate_label(b: &mut Bencher) {
+    b.iter(|| bench::iter_named().count());
+}
+
+fn main() {
+    label::bench::main(bench::iter_named());
+}
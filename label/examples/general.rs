@@ -30,7 +30,7 @@ pub mod folder {
             println!("Test3!");
         }
 
-        create_label!(fn test1() -> ());
+        create_label!(pub fn test1() -> ());
     }
 }
 
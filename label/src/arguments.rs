@@ -0,0 +1,61 @@
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "the `{Key}` argument of `{Item}` must be an integer, because of its label `{Label}`",
+    label = "expected an integer",
+    note = "labels declare integer arguments with `#[label(integer = ..)]`, event labels declare `priority`"
+)]
+// Checks an argument which isn't an integer. Labels declare their integer arguments, and `Item`,
+// `Label` and `Key` are types named after the item, the label and the argument, like in `__Visible`.
+// `REJECTED` is whether the label declared the argument.
+pub trait __Integer<Item, Label, Key, const REJECTED: bool> {}
+
+impl<Item, Label, Key> __Integer<Item, Label, Key, false> for () {}
+
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "the `{Key}` argument of `{Item}` must be a non-negative integer, because of its label `{Label}`",
+    label = "expected a non-negative integer",
+    note = "labels declare non-negative integer arguments with `#[label(unsigned = ..)]`"
+)]
+// Checks an argument which isn't a non-negative integer, like `__Integer`.
+pub trait __Unsigned<Item, Label, Key, const REJECTED: bool> {}
+
+impl<Item, Label, Key> __Unsigned<Item, Label, Key, false> for () {}
+
+#[doc(hidden)]
+// Used where items are labeled with an argument which isn't an integer.
+pub fn __integer<Item, Label, Key, const REJECTED: bool>()
+where
+    (): __Integer<Item, Label, Key, REJECTED>,
+{
+}
+
+#[doc(hidden)]
+// Used where items are labeled with an argument which isn't a non-negative integer.
+pub fn __unsigned<Item, Label, Key, const REJECTED: bool>()
+where
+    (): __Unsigned<Item, Label, Key, REJECTED>,
+{
+}
+
+#[doc(hidden)]
+// Whether `key` is one of the arguments a label declared, in the check of an argument.
+pub const fn __declares(arguments: &[&str], key: &str) -> bool {
+    let key = key.as_bytes();
+    let mut index = 0;
+    while index < arguments.len() {
+        let argument = arguments[index].as_bytes();
+        if argument.len() == key.len() {
+            let mut i = 0;
+            while i < key.len() && argument[i] == key[i] {
+                i += 1;
+            }
+            if i == key.len() {
+                return true;
+            }
+        }
+        index += 1;
+    }
+
+    false
+}
@@ -0,0 +1,584 @@
+//! A benchmark runner for `harness = false` bench targets, which times every function with a label.
+//!
+//! This works on stable rust. Add a bench target without the default harness to your `Cargo.toml`:
+//!
+//! ```toml
+//! [[bench]]
+//! name = "parsing"
+//! harness = false
+//! ```
+//!
+//! And hand the functions of a label to [`main`] in `benches/parsing.rs`:
+//!
+//! ```no_run
+//! use label::bench::Bencher;
+//! use label::create_label;
+//!
+//! create_label!(fn bench(&mut Bencher));
+//!
+//! #[bench::label]
+//! fn parse_number(b: &mut Bencher) {
+//!     b.iter(|| "12345".parse::<usize>());
+//! }
+//!
+//! fn main() {
+//!     label::bench::main(bench::iter_named());
+//! }
+//! ```
+//!
+//! Every benchmark is first warmed up, after which a number of samples is taken.
+//! The mean, median and standard deviation of the time per iteration are reported.
+//!
+//! The runner takes the following arguments:
+//!
+//! * name filters, and `--exact` to match names exactly
+//! * `--list` to list the benchmarks instead of running them
+//! * `--format pretty|json|csv` to choose how results are written
+//! * `--warmup <ms>`, `--measurement-time <ms>` and `--samples <n>`
+//! * `--save-baseline <file>` to store the results as csv,
+//!   and `--baseline <file>` to compare the results against such a file
+//!
+//! Like the default harness, benchmarks are only timed when `--bench` is passed, which `cargo bench` does.
+//! Otherwise, like with `cargo test --benches`, every benchmark runs once to check that it works.
+
+use crate::catch::catch;
+use crate::json;
+use crate::Entry;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::hint::black_box;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Passed to every benchmark to time a piece of code.
+pub struct Bencher {
+    mode: Mode,
+    samples: Vec<f64>,
+    iterations: u64,
+}
+
+enum Mode {
+    Measure {
+        warmup: Duration,
+        measurement_time: Duration,
+        samples: usize,
+    },
+    Once,
+}
+
+impl Bencher {
+    /// Times `f`. The value returned by `f` is passed through [`black_box`],
+    /// so the computation is not optimized away.
+    pub fn iter<T>(&mut self, mut f: impl FnMut() -> T) {
+        let (warmup, measurement_time, samples) = match self.mode {
+            Mode::Measure {
+                warmup,
+                measurement_time,
+                samples,
+            } => (warmup, measurement_time, samples),
+            Mode::Once => {
+                black_box(f());
+                return;
+            }
+        };
+
+        let start = Instant::now();
+        let mut warmup_iterations = 0u64;
+        while start.elapsed() < warmup || warmup_iterations == 0 {
+            black_box(f());
+            warmup_iterations += 1;
+        }
+        let estimate = start.elapsed().as_nanos() as f64 / warmup_iterations as f64;
+
+        let per_sample = measurement_time.as_nanos() as f64 / samples as f64;
+        let iterations = ((per_sample / estimate.max(1.0)) as u64).max(1);
+
+        self.iterations = iterations;
+        self.samples = (0..samples)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    black_box(f());
+                }
+                start.elapsed().as_nanos() as f64 / iterations as f64
+            })
+            .collect();
+    }
+}
+
+/// A single benchmark, usually created from an item of a label's `iter_named()`.
+pub struct Bench {
+    name: String,
+    func: fn(&mut Bencher),
+}
+
+impl Bench {
+    pub fn new(name: impl Into<String>, func: fn(&mut Bencher)) -> Self {
+        Self {
+            name: name.into(),
+            func,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<(&'static str, fn(&mut Bencher))> for Bench {
+    fn from((name, func): (&'static str, fn(&mut Bencher))) -> Self {
+        Bench::new(name, func)
+    }
+}
+
+impl From<Entry<fn(&mut Bencher)>> for Bench {
+    fn from(entry: Entry<fn(&mut Bencher)>) -> Self {
+        Bench::new(entry.name(), entry.item())
+    }
+}
+
+/// The output format of the runner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// One human readable line per benchmark.
+    #[default]
+    Pretty,
+    /// A json array with one object per benchmark.
+    Json,
+    /// Comma separated values with a header, the same format as `--save-baseline`.
+    Csv,
+}
+
+/// Command line arguments of the runner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Arguments {
+    /// Only run benchmarks whose name contains one of these.
+    pub filters: Vec<String>,
+    /// Filters have to match the benchmark name exactly.
+    pub exact: bool,
+    /// List the benchmarks instead of running them.
+    pub list: bool,
+    /// Time the benchmarks. Otherwise every benchmark runs once, without timing it.
+    pub bench: bool,
+    pub format: Format,
+    pub warmup: Duration,
+    /// The total time spent taking samples, per benchmark.
+    pub measurement_time: Duration,
+    pub samples: usize,
+    /// Where to write the results as csv.
+    pub save_baseline: Option<PathBuf>,
+    /// A file written with `save_baseline` to compare the results with.
+    pub baseline: Option<PathBuf>,
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        Self {
+            filters: Vec::new(),
+            exact: false,
+            list: false,
+            bench: false,
+            format: Format::Pretty,
+            warmup: Duration::from_millis(500),
+            measurement_time: Duration::from_secs(2),
+            samples: 50,
+            save_baseline: None,
+            baseline: None,
+        }
+    }
+}
+
+impl Arguments {
+    /// Parses the arguments the program was started with.
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses arguments, not including the program name.
+    pub fn parse<I, S>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut res = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                res.filters.push(arg);
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.find('=') {
+                Some(i) => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+                None => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("argument to option '{}' missing", flag))
+            };
+            let number = |value: String| {
+                value.parse::<u64>().ok().filter(|i| *i > 0).ok_or_else(|| {
+                    format!("argument for {} must be a number > 0 (was {})", flag, value)
+                })
+            };
+
+            match flag.as_str() {
+                "--exact" => res.exact = true,
+                "--list" => res.list = true,
+                "--bench" => res.bench = true,
+                "--format" => {
+                    res.format = match value()?.as_str() {
+                        "pretty" => Format::Pretty,
+                        "json" => Format::Json,
+                        "csv" => Format::Csv,
+                        other => {
+                            return Err(format!(
+                                "argument for --format must be pretty, json, or csv (was {})",
+                                other
+                            ))
+                        }
+                    }
+                }
+                "--warmup" => res.warmup = Duration::from_millis(number(value()?)?),
+                "--measurement-time" => {
+                    res.measurement_time = Duration::from_millis(number(value()?)?)
+                }
+                "--samples" => res.samples = number(value()?)? as usize,
+                "--save-baseline" => res.save_baseline = Some(value()?.into()),
+                "--baseline" => res.baseline = Some(value()?.into()),
+                "--test" => res.bench = false,
+                _ => return Err(format!("unrecognized option: '{}'", flag)),
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.filters.is_empty()
+            || self.filters.iter().any(|filter| {
+                if self.exact {
+                    name == filter
+                } else {
+                    name.contains(filter.as_str())
+                }
+            })
+    }
+}
+
+/// The time per iteration of a benchmark, in nanoseconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub name: String,
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    pub samples: usize,
+    /// Iterations per sample.
+    pub iterations: u64,
+    /// The mean of this benchmark in the baseline, if one was given and it contains this benchmark.
+    pub baseline: Option<f64>,
+}
+
+impl Measurement {
+    fn from_samples(name: &str, mut samples: Vec<f64>, iterations: u64) -> Self {
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let median = if samples.len() % 2 == 1 {
+            samples[samples.len() / 2]
+        } else {
+            (samples[samples.len() / 2 - 1] + samples[samples.len() / 2]) / 2.0
+        };
+        let variance = if samples.len() > 1 {
+            samples.iter().map(|i| (i - mean).powi(2)).sum::<f64>() / (n - 1.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            name: name.to_string(),
+            mean,
+            median,
+            stddev: variance.sqrt(),
+            samples: samples.len(),
+            iterations,
+            baseline: None,
+        }
+    }
+
+    /// The relative change of the mean compared to the baseline, `0.1` meaning 10% slower.
+    pub fn change(&self) -> Option<f64> {
+        self.baseline.map(|i| (self.mean - i) / i)
+    }
+}
+
+/// The results of a run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Conclusion {
+    pub measurements: Vec<Measurement>,
+    /// Benchmarks which panicked, with the panic message.
+    pub failed: Vec<(String, String)>,
+    pub filtered_out: usize,
+}
+
+impl Conclusion {
+    pub fn has_failed(&self) -> bool {
+        !self.failed.is_empty()
+    }
+}
+
+fn format_time(nanos: f64) -> String {
+    if nanos < 1e3 {
+        format!("{:.2} ns", nanos)
+    } else if nanos < 1e6 {
+        format!("{:.2} µs", nanos / 1e3)
+    } else if nanos < 1e9 {
+        format!("{:.2} ms", nanos / 1e6)
+    } else {
+        format!("{:.2} s", nanos / 1e9)
+    }
+}
+
+const CSV_HEADER: &str = "name,mean_ns,median_ns,stddev_ns,samples,iterations";
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn csv_row(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+/// Formats measurements as csv, the format used for baselines.
+pub fn to_csv(measurements: &[Measurement]) -> String {
+    let mut res = format!("{}\n", CSV_HEADER);
+    for i in measurements {
+        let _ = writeln!(
+            res,
+            "{},{},{},{},{},{}",
+            csv_field(&i.name),
+            i.mean,
+            i.median,
+            i.stddev,
+            i.samples,
+            i.iterations
+        );
+    }
+    res
+}
+
+/// Formats measurements as a json array.
+pub fn to_json(measurements: &[Measurement]) -> String {
+    let optional = |i: Option<f64>| i.map_or("null".to_string(), |i| i.to_string());
+
+    let objects: Vec<_> = measurements
+        .iter()
+        .map(|i| {
+            format!(
+                r#"  {{ "name": {}, "mean_ns": {}, "median_ns": {}, "stddev_ns": {}, "samples": {}, "iterations": {}, "baseline_mean_ns": {}, "change": {} }}"#,
+                json::string(&i.name),
+                i.mean,
+                i.median,
+                i.stddev,
+                i.samples,
+                i.iterations,
+                optional(i.baseline),
+                optional(i.change()),
+            )
+        })
+        .collect();
+
+    if objects.is_empty() {
+        return "[]\n".to_string();
+    }
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
+
+/// Reads the mean of every benchmark from csv written by [`to_csv`] or `--save-baseline`.
+pub fn parse_baseline(csv: &str) -> Result<HashMap<String, f64>, String> {
+    let mut lines = csv.lines().filter(|i| !i.trim().is_empty());
+    let header = csv_row(lines.next().ok_or("baseline is empty")?);
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|i| i == name)
+            .ok_or_else(|| format!("baseline has no {} column", name))
+    };
+    let (name_column, mean_column) = (column("name")?, column("mean_ns")?);
+
+    lines
+        .enumerate()
+        .map(|(line, row)| {
+            let row = csv_row(row);
+            match (row.get(name_column), row.get(mean_column)) {
+                (Some(name), Some(mean)) => mean
+                    .parse()
+                    .map(|mean| (name.clone(), mean))
+                    .map_err(|_| format!("invalid mean on line {} of baseline", line + 2)),
+                _ => Err(format!("missing column on line {} of baseline", line + 2)),
+            }
+        })
+        .collect()
+}
+
+/// Runs `benches` according to `args`, writing the results to `out`.
+///
+/// Benchmarks are ordered by name. Only an error writing to `out`, or reading or writing
+/// the baseline files makes this return an error. Panicking benchmarks are reported in the
+/// [`Conclusion`].
+pub fn run<T, W>(
+    args: &Arguments,
+    benches: impl IntoIterator<Item = T>,
+    mut out: W,
+) -> io::Result<Conclusion>
+where
+    T: Into<Bench>,
+    W: Write,
+{
+    let mut benches: Vec<Bench> = benches.into_iter().map(Into::into).collect();
+    benches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let total = benches.len();
+    benches.retain(|i| args.matches(&i.name));
+    let mut conclusion = Conclusion {
+        filtered_out: total - benches.len(),
+        ..Conclusion::default()
+    };
+
+    if args.list {
+        for bench in &benches {
+            writeln!(out, "{}: bench", bench.name)?;
+        }
+        return Ok(conclusion);
+    }
+
+    let baseline = match &args.baseline {
+        Some(path) => parse_baseline(&fs::read_to_string(path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        None => HashMap::new(),
+    };
+
+    for bench in &benches {
+        let mut bencher = Bencher {
+            mode: if args.bench {
+                Mode::Measure {
+                    warmup: args.warmup,
+                    measurement_time: args.measurement_time,
+                    samples: args.samples,
+                }
+            } else {
+                Mode::Once
+            },
+            samples: Vec::new(),
+            iterations: 0,
+        };
+
+        if let Err(panic) = catch(|| (bench.func)(&mut bencher)) {
+            if args.format == Format::Pretty {
+                writeln!(out, "{:<30} FAILED: {}", bench.name, panic.message)?;
+            }
+            conclusion.failed.push((bench.name.clone(), panic.message));
+            continue;
+        }
+
+        if !args.bench {
+            if args.format == Format::Pretty {
+                writeln!(out, "{:<30} ok", bench.name)?;
+            }
+            continue;
+        }
+
+        if bencher.samples.is_empty() {
+            if args.format == Format::Pretty {
+                writeln!(
+                    out,
+                    "{:<30} no measurement, Bencher::iter was not called",
+                    bench.name
+                )?;
+            }
+            continue;
+        }
+
+        let mut measurement =
+            Measurement::from_samples(&bench.name, bencher.samples, bencher.iterations);
+        measurement.baseline = baseline.get(&bench.name).copied();
+
+        if args.format == Format::Pretty {
+            write!(
+                out,
+                "{:<30} time: mean {}  median {}  stddev {}",
+                measurement.name,
+                format_time(measurement.mean),
+                format_time(measurement.median),
+                format_time(measurement.stddev),
+            )?;
+            if let Some(change) = measurement.change() {
+                write!(out, "  change: {:+.2}%", change * 100.0)?;
+            }
+            writeln!(out)?;
+            out.flush()?;
+        }
+
+        conclusion.measurements.push(measurement);
+    }
+
+    match args.format {
+        Format::Pretty => {}
+        Format::Json => write!(out, "{}", to_json(&conclusion.measurements))?,
+        Format::Csv => write!(out, "{}", to_csv(&conclusion.measurements))?,
+    }
+
+    if let Some(path) = &args.save_baseline {
+        fs::write(path, to_csv(&conclusion.measurements))?;
+    }
+
+    Ok(conclusion)
+}
+
+/// Parses the command line arguments, runs `benches` and exits the process,
+/// with exit code 101 if any benchmark panicked.
+pub fn main<T: Into<Bench>>(benches: impl IntoIterator<Item = T>) -> ! {
+    let args = match Arguments::from_args() {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(101);
+        }
+    };
+
+    match run(&args, benches, io::stdout()) {
+        Ok(conclusion) if !conclusion.has_failed() => std::process::exit(0),
+        Ok(_) => std::process::exit(101),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(101);
+        }
+    }
+}
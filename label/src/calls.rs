@@ -0,0 +1,223 @@
+//! Support for the functions which call every function with a label.
+//!
+//! Every function label gets a `call_all` function, which calls all labeled functions
+//! with the same arguments and returns their names and return values:
+//!
+//! ```
+//! use label::create_label;
+//!
+//! create_label!(
+//!     fn double(usize) -> usize;
+//!     fn parse(&str) -> Result<u8, std::num::ParseIntError>;
+//!     fn lookup(&str) -> Option<&'static str>;
+//! );
+//!
+//! #[double::label]
+//! fn add(x: usize) -> usize {
+//!     x + x
+//! }
+//!
+//! #[parse::label]
+//! fn decimal(s: &str) -> Result<u8, std::num::ParseIntError> {
+//!     s.parse()
+//! }
+//!
+//! #[lookup::label]
+//! fn greeting(key: &str) -> Option<&'static str> {
+//!     if key == "hello" {
+//!         Some("world")
+//!     } else {
+//!         None
+//!     }
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(double::call_all(21), vec![("add", 42)]);
+//!
+//!     assert_eq!(parse::try_call_all("12"), Ok(vec![("decimal", 12)]));
+//!     assert!(parse::call_all_collect_errors("twelve").is_err());
+//!
+//!     assert_eq!(lookup::call_first_some("hello"), Some(("greeting", "world")));
+//! }
+//! ```
+//!
+//! Labels returning a `Result` also get `try_call_all`, which stops at the first error,
+//! and `call_all_collect_errors`, which calls every function and returns all errors if there were any.
+//! Labels returning an `Option` get `call_first_some`, which calls functions until one returns `Some`.
+//! Whether a label returns a `Result` or `Option` is decided by the name of its return type, and the
+//! functions can only be called when that type implements [`Fallible`] or [`Optional`].
+//!
+//! Every function is called with its own copy of the arguments: references are passed as they are,
+//! `&mut` references are reborrowed and other arguments are cloned. These functions can only be called
+//! when all arguments can be cloned, and are not generated when the return type borrows from a `&mut` argument.
+//!
+//! When a labeled function panics, the functions after it are not called. To call every function
+//! regardless, use `call_all_isolated`, which catches panics and returns an [`Outcome`] per function:
+//!
+//! ```
+//! use label::create_label;
+//!
+//! create_label!(fn divide(u32) -> u32);
+//!
+//! #[divide::label]
+//! fn hundred(x: u32) -> u32 {
+//!     100 / x
+//! }
+//!
+//! fn main() {
+//!     for outcome in divide::call_all_isolated(0) {
+//!         match outcome.result {
+//!             Ok(value) => println!("{} returned {}", outcome.name, value),
+//!             Err(panic) => println!("{} (at {}) {}", outcome.name, outcome.location, panic),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Panics can only be caught when compiling with `panic = "unwind"` (the default). With `panic = "abort"`,
+//! calling `call_all_isolated` is a compile error.
+
+use crate::catch::catch;
+pub use crate::catch::Panic;
+use crate::{Entry, Location};
+
+type Named<T> = Vec<(&'static str, T)>;
+
+/// Return types which can fail, like `Result`.
+pub trait Fallible {
+    type Value;
+    type Error;
+
+    fn into_result(self) -> Result<Self::Value, Self::Error>;
+}
+
+impl<T, E> Fallible for Result<T, E> {
+    type Value = T;
+    type Error = E;
+
+    fn into_result(self) -> Result<Self::Value, Self::Error> {
+        self
+    }
+}
+
+/// Return types which may not have a value, like `Option`.
+pub trait Optional {
+    type Value;
+
+    fn into_option(self) -> Option<Self::Value>;
+}
+
+impl<T> Optional for Option<T> {
+    type Value = T;
+
+    fn into_option(self) -> Option<Self::Value> {
+        self
+    }
+}
+
+/// The result of calling a single labeled function with `call_all_isolated`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome<R> {
+    pub name: &'static str,
+    /// Where the function was labeled.
+    pub location: Location,
+    /// The return value of the function, or the panic if it panicked.
+    pub result: Result<R, Panic>,
+}
+
+impl<R> Outcome<R> {
+    pub fn panicked(&self) -> bool {
+        self.result.is_err()
+    }
+}
+
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`call_all_isolated` can only be used when compiling with `panic = \"unwind\"`",
+    label = "panics can't be caught here",
+    note = "with `panic = \"abort\"`, the process aborts at the first panic"
+)]
+// Only implemented when panics can be caught, `call_all_isolated` requires it.
+pub trait __Unwinding {}
+
+#[cfg(panic = "unwind")]
+impl __Unwinding for () {}
+
+#[doc(hidden)]
+// Used by the `call_all` function generated for function labels.
+pub fn __call_all<H: Copy, R>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+    mut call: impl FnMut(H) -> R,
+) -> Vec<(&'static str, R)> {
+    entries
+        .into_iter()
+        .map(|entry| (entry.name(), call(entry.item())))
+        .collect()
+}
+
+#[doc(hidden)]
+// Used by the `try_call_all` function generated for function labels.
+pub fn __try_call_all<H: Copy, R: Fallible>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+    mut call: impl FnMut(H) -> R,
+) -> Result<Named<R::Value>, (&'static str, R::Error)> {
+    entries
+        .into_iter()
+        .map(|entry| {
+            call(entry.item())
+                .into_result()
+                .map(|i| (entry.name(), i))
+                .map_err(|e| (entry.name(), e))
+        })
+        .collect()
+}
+
+#[doc(hidden)]
+// Used by the `call_all_collect_errors` function generated for function labels.
+pub fn __call_all_collect_errors<H: Copy, R: Fallible>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+    mut call: impl FnMut(H) -> R,
+) -> Result<Named<R::Value>, Named<R::Error>> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+
+    for entry in entries {
+        match call(entry.item()).into_result() {
+            Ok(i) => values.push((entry.name(), i)),
+            Err(e) => errors.push((entry.name(), e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+#[doc(hidden)]
+// Used by the `call_first_some` function generated for function labels.
+pub fn __call_first_some<H: Copy, R: Optional>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+    mut call: impl FnMut(H) -> R,
+) -> Option<(&'static str, R::Value)> {
+    entries
+        .into_iter()
+        .find_map(|entry| call(entry.item()).into_option().map(|i| (entry.name(), i)))
+}
+
+#[doc(hidden)]
+// Used by the `call_all_isolated` function generated for function labels.
+pub fn __call_all_isolated<H: Copy, R>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+    mut call: impl FnMut(H) -> R,
+) -> Vec<Outcome<R>> {
+    entries
+        .into_iter()
+        .map(|entry| Outcome {
+            name: entry.name(),
+            location: entry.location(),
+            result: catch(|| call(entry.item())),
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+/// A caught panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panic {
+    /// The panic message, or `Box<dyn Any>` when the panic payload is not a string.
+    pub message: String,
+    /// Where the panic happened, as `file:line:column`.
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "panicked at {}: {}", location, self.message),
+            None => write!(f, "panicked: {}", self.message),
+        }
+    }
+}
+
+thread_local! {
+    // set while inside `catch`, so the panic hook knows not to print anything.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn install_hook() {
+    static INSTALL: Once = Once::new();
+
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.with(Cell::get) {
+                let location = info.location().map(|i| i.to_string());
+                LOCATION.with(|i| *i.borrow_mut() = location);
+            } else {
+                previous(info);
+            }
+        }));
+    });
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(i) = payload.downcast_ref::<&str>() {
+        (*i).to_string()
+    } else if let Some(i) = payload.downcast_ref::<String>() {
+        i.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+/// Runs `f`, catching any panic inside it instead of printing it.
+///
+/// If the panic hook is replaced after the first call to this function,
+/// panics are still caught but the location of the panic is lost.
+pub(crate) fn catch<R>(f: impl FnOnce() -> R) -> Result<R, Panic> {
+    install_hook();
+
+    let was_catching = CATCHING.with(|i| i.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|i| i.set(was_catching));
+
+    result.map_err(|payload| Panic {
+        message: payload_message(&*payload),
+        location: LOCATION.with(|i| i.borrow_mut().take()),
+    })
+}
@@ -0,0 +1,217 @@
+//! A subcommand dispatcher, where every function with a label is a subcommand.
+//!
+//! ```no_run
+//! use label::cli::Dispatcher;
+//! use label::create_label;
+//!
+//! create_label!(fn command(&[String]) -> i32);
+//!
+//! /// Deploys the arguments.
+//! #[command::label]
+//! fn deploy(args: &[String]) -> i32 {
+//!     println!("deploying {:?}", args);
+//!     0
+//! }
+//!
+//! fn main() {
+//!     Dispatcher::new(command::iter_entries()).run();
+//! }
+//! ```
+//!
+//! `tool deploy production` calls `deploy` with `["production"]`, and the process exits
+//! with the value it returns. `tool --help` lists all commands, together with the first line
+//! of their doc comment. When no command is given, or the command doesn't exist, the process
+//! exits with [`USAGE_ERROR`], and commands with a similar name are suggested.
+
+use crate::Entry;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The exit code when the arguments did not name an existing command.
+pub const USAGE_ERROR: i32 = 2;
+
+/// A single subcommand, usually created from an item of a label's `iter_entries()` or `iter_named()`.
+pub struct Command {
+    name: String,
+    description: Option<String>,
+    func: fn(&[String]) -> i32,
+}
+
+impl Command {
+    pub fn new(name: impl Into<String>, func: fn(&[String]) -> i32) -> Self {
+        Self {
+            name: name.into(),
+            description: None,
+            func,
+        }
+    }
+
+    /// The description shown next to the name of the command in `--help`.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+impl From<(&'static str, fn(&[String]) -> i32)> for Command {
+    fn from((name, func): (&'static str, fn(&[String]) -> i32)) -> Self {
+        Command::new(name, func)
+    }
+}
+
+impl From<Entry<fn(&[String]) -> i32>> for Command {
+    fn from(entry: Entry<fn(&[String]) -> i32>) -> Self {
+        let res = Command::new(entry.name(), entry.item());
+        match entry.doc().lines().next() {
+            Some(summary) if !summary.trim().is_empty() => res.with_description(summary.trim()),
+            _ => res,
+        }
+    }
+}
+
+/// Calls the command named by the first argument.
+pub struct Dispatcher {
+    commands: Vec<Command>,
+}
+
+impl Dispatcher {
+    /// Creates a dispatcher for `commands`.
+    ///
+    /// # Panics
+    ///
+    /// When two commands have the same name, as only one of them could ever be called.
+    pub fn new<T: Into<Command>>(commands: impl IntoIterator<Item = T>) -> Self {
+        let mut commands: Vec<Command> = commands.into_iter().map(Into::into).collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for i in commands.windows(2) {
+            if i[0].name == i[1].name {
+                panic!("there is more than one command named `{}`", i[0].name);
+            }
+        }
+
+        Self { commands }
+    }
+
+    pub fn commands(&self) -> &[Command] {
+        &self.commands
+    }
+
+    /// The list of commands shown by `--help`.
+    pub fn help(&self, program: &str) -> String {
+        // the built-in `help` is only listed when no command replaces it.
+        let builtin = (!self.commands.iter().any(|i| i.name == "help"))
+            .then_some(("help", "Print this message"));
+        let width = self
+            .commands
+            .iter()
+            .map(|i| i.name.len())
+            .chain(Some("help".len()))
+            .max()
+            .unwrap_or(0);
+
+        let mut res = format!("Usage: {} <command> [arguments...]\n\nCommands:\n", program);
+        for (name, description) in self
+            .commands
+            .iter()
+            .map(|i| (i.name.as_str(), i.description.as_deref().unwrap_or("")))
+            .chain(builtin)
+        {
+            let line = format!("    {:<width$}  {}", name, description, width = width);
+            res.push_str(line.trim_end());
+            res.push('\n');
+        }
+
+        res
+    }
+
+    /// The names of commands which are close to `name`, closest first.
+    pub fn suggest(&self, name: &str) -> Vec<&str> {
+        let mut res: Vec<_> = self
+            .commands
+            .iter()
+            .map(|i| (edit_distance(name, &i.name), i.name.as_str()))
+            .filter(|(distance, i)| *distance <= (i.chars().count() / 3).max(1))
+            .collect();
+
+        res.sort();
+        res.into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// Dispatches on `args` (including the program name) and returns the exit code.
+    /// Help goes to `out`, errors go to `err`.
+    pub fn dispatch<I, S>(&self, args: I, mut out: impl Write, mut err: impl Write) -> i32
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let program = args
+            .next()
+            .map(|i| {
+                Path::new(&i)
+                    .file_stem()
+                    .map_or(i.clone(), |i| i.to_string_lossy().into_owned())
+            })
+            .unwrap_or_default();
+
+        let command = match args.next() {
+            Some(i) => i,
+            None => {
+                let _ = write!(err, "{}", self.help(&program));
+                return USAGE_ERROR;
+            }
+        };
+        let rest: Vec<String> = args.collect();
+
+        if let Some(i) = self.commands.iter().find(|i| i.name == command) {
+            return (i.func)(&rest);
+        }
+
+        match command.as_str() {
+            "help" | "--help" | "-h" => {
+                let _ = write!(out, "{}", self.help(&program));
+                0
+            }
+            _ => {
+                let _ = writeln!(err, "error: unknown command `{}`", command);
+                if let Some(suggestion) = self.suggest(&command).first() {
+                    let _ = writeln!(err, "\n  tip: a similar command exists: `{}`", suggestion);
+                }
+                let _ = writeln!(err, "\nRun `{} --help` for a list of commands.", program);
+                USAGE_ERROR
+            }
+        }
+    }
+
+    /// Dispatches on the arguments the program was started with, and exits the
+    /// process with the returned exit code.
+    pub fn run(&self) -> ! {
+        let code = self.dispatch(std::env::args(), io::stdout(), io::stderr());
+        std::process::exit(code)
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
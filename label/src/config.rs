@@ -0,0 +1,392 @@
+//! Configuration keys, where every key is a labeled static.
+//!
+//! Keys are declared as statics without a value, which is loaded later from the environment
+//! or a TOML file:
+//!
+//! ```
+//! use label::config::{self, ConfigValue};
+//!
+//! /// The port to listen on.
+//! #[config::label(env = "PORT", default = 8080)]
+//! static PORT: ConfigValue<u16>;
+//!
+//! /// The name of the server.
+//! #[config::label(key = "server.name")]
+//! static NAME: ConfigValue<String>;
+//!
+//! fn main() {
+//!     let file = r#"
+//!         [server]
+//!         name = "label"
+//!     "#;
+//!     config::load_from(vec![("PORT".to_string(), "3000".to_string())], file).unwrap();
+//!
+//!     assert_eq!(PORT.get(), 3000);
+//!     assert_eq!(NAME.get(), "label");
+//!
+//!     print!("{}", config::describe());
+//! }
+//! ```
+//!
+//! Every key is named after its static in lowercase, unless a `key` argument is given. Values from
+//! the environment variable given with `env` take precedence over values from the file, which take
+//! precedence over the `default` argument. Values are parsed with `FromStr`.
+//!
+//! Only a subset of TOML is supported: tables, and keys with strings, numbers or booleans as values.
+
+use crate::create_label;
+use crate::Entry;
+use std::any::type_name;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::sync::{PoisonError, RwLock};
+
+/// A labeled configuration key, which can be set from a string.
+pub trait Key: Sync {
+    /// The name of the type of the value.
+    fn type_name(&self) -> &'static str;
+
+    /// Parses `value` and sets the key to it.
+    fn set_str(&self, value: &str) -> Result<(), String>;
+}
+
+mod registry {
+    use super::*;
+
+    create_label!(
+        pub static keys: dyn Key;
+    );
+}
+
+pub use self::registry::keys::{add, iter, iter_entries, iter_named, label};
+
+/// The value of a configuration key, which is set by [`load_from`].
+#[derive(Debug, Default)]
+pub struct ConfigValue<T> {
+    value: RwLock<Option<T>>,
+}
+
+impl<T> ConfigValue<T> {
+    pub const fn new() -> Self {
+        Self {
+            value: RwLock::new(None),
+        }
+    }
+
+    pub fn set(&self, value: T) {
+        *self.value.write().unwrap_or_else(PoisonError::into_inner) = Some(value);
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.value
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+}
+
+impl<T: Clone> ConfigValue<T> {
+    /// The value, or `None` if it was not loaded yet.
+    pub fn try_get(&self) -> Option<T> {
+        self.value
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// The value.
+    ///
+    /// # Panics
+    ///
+    /// When the value was not loaded yet.
+    pub fn get(&self) -> T {
+        self.try_get()
+            .expect("configuration value used before it was loaded")
+    }
+}
+
+impl<T> Key for ConfigValue<T>
+where
+    T: FromStr + Send + Sync,
+    T::Err: fmt::Display,
+{
+    fn type_name(&self) -> &'static str {
+        type_name::<T>()
+    }
+
+    fn set_str(&self, value: &str) -> Result<(), String> {
+        self.set(value.parse().map_err(|e: T::Err| e.to_string())?);
+        Ok(())
+    }
+}
+
+/// A problem found by [`load_from`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A line of the file which is not valid (supported) TOML.
+    Syntax { line: usize, message: String },
+    /// A key in the file which is not labeled.
+    Unknown { key: String, line: usize },
+    /// A key without a value in the environment or file, and without a default.
+    Missing { key: String },
+    /// A key used by multiple statics, like two statics named `PORT` in different modules.
+    Duplicate { key: String },
+    /// A value which could not be parsed as the type of its key.
+    Invalid {
+        key: String,
+        value: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            ConfigError::Unknown { key, line } => {
+                write!(f, "line {}: unknown key `{}`", line, key)
+            }
+            ConfigError::Missing { key } => write!(f, "no value for key `{}`", key),
+            ConfigError::Duplicate { key } => write!(f, "multiple statics use key `{}`", key),
+            ConfigError::Invalid {
+                key,
+                value,
+                message,
+            } => write!(
+                f,
+                "invalid value `{}` for key `{}`: {}",
+                value, key, message
+            ),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// The name of a labeled key: its `key` argument, or the lowercased name of the static.
+pub fn key(entry: &Entry<&'static dyn Key>) -> String {
+    match entry.meta("key") {
+        Some(key) => key.to_string(),
+        None => entry.name().to_lowercase(),
+    }
+}
+
+fn parse_string(value: &str, line: usize) -> Result<String, ConfigError> {
+    let syntax = |message: &str| ConfigError::Syntax {
+        line,
+        message: message.to_string(),
+    };
+
+    if let Some(literal) = value.strip_prefix('\'') {
+        return literal
+            .strip_suffix('\'')
+            .map(str::to_string)
+            .ok_or_else(|| syntax("unterminated string"));
+    }
+
+    let mut res = String::new();
+    let mut chars = value[1..].chars();
+    loop {
+        match chars.next() {
+            None => return Err(syntax("unterminated string")),
+            Some('"') if chars.as_str().is_empty() => return Ok(res),
+            Some('"') => return Err(syntax("unexpected text after string")),
+            Some('\\') => res.push(match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some('"') => '"',
+                Some('\\') => '\\',
+                _ => return Err(syntax("unsupported escape in string")),
+            }),
+            Some(c) => res.push(c),
+        }
+    }
+}
+
+/// Removes a comment from a line, if the `#` is not inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => {
+                escaped = true;
+                continue;
+            }
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+/// Whether `value` looks like a TOML number, like `-1_000`, `0.5`, `1e6` or `inf`.
+fn is_number(value: &str) -> bool {
+    let digits = value.trim_start_matches(['+', '-']);
+    matches!(digits, "inf" | "nan")
+        || digits.starts_with(|c: char| c.is_ascii_digit())
+            && digits
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-'))
+}
+
+/// Parses the supported subset of TOML to `(key, value, line)`.
+fn parse_toml(toml: &str) -> Result<Vec<(String, String, usize)>, ConfigError> {
+    let mut table = String::new();
+    let mut res = Vec::new();
+
+    for (index, line) in toml.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name.strip_suffix(']').ok_or(ConfigError::Syntax {
+                line: line_number,
+                message: "expected `]`".to_string(),
+            })?;
+            table = format!("{}.", name.trim());
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or(ConfigError::Syntax {
+            line: line_number,
+            message: "expected `key = value`".to_string(),
+        })?;
+        let value = value.trim();
+        let value = if value.starts_with('"') || value.starts_with('\'') {
+            parse_string(value, line_number)?
+        } else if value.starts_with('[') || value.starts_with('{') {
+            return Err(ConfigError::Syntax {
+                line: line_number,
+                message: "arrays and inline tables are not supported".to_string(),
+            });
+        } else if value == "true" || value == "false" {
+            value.to_string()
+        } else if is_number(value) {
+            value.replace('_', "")
+        } else {
+            return Err(ConfigError::Syntax {
+                line: line_number,
+                message: format!("expected a string, number or boolean, not `{}`", value),
+            });
+        };
+
+        res.push((format!("{}{}", table, key.trim()), value, line_number));
+    }
+
+    Ok(res)
+}
+
+/// Sets `entries` from environment variables `env` and the TOML file `toml`.
+///
+/// Keys with a valid value are set, even when other keys have problems.
+/// Keys used by multiple statics are not set.
+pub fn load_entries(
+    entries: impl IntoIterator<Item = Entry<&'static dyn Key>>,
+    env: impl IntoIterator<Item = (String, String)>,
+    toml: &str,
+) -> Result<(), Vec<ConfigError>> {
+    let mut errors = Vec::new();
+    let env: HashMap<_, _> = env.into_iter().collect();
+    let mut duplicates = HashSet::new();
+    let mut by_key = HashMap::new();
+    for entry in entries {
+        let key = key(&entry);
+        if by_key.insert(key.clone(), entry).is_some() {
+            duplicates.insert(key);
+        }
+    }
+    let entries = by_key;
+
+    let mut file = HashMap::new();
+    match parse_toml(toml) {
+        Ok(values) => {
+            for (key, value, line) in values {
+                if entries.contains_key(&key) {
+                    file.insert(key, value);
+                } else {
+                    errors.push(ConfigError::Unknown { key, line });
+                }
+            }
+        }
+        Err(e) => errors.push(e),
+    }
+
+    let mut keys: Vec<_> = entries.keys().collect();
+    keys.sort();
+    for key in keys {
+        if duplicates.contains(key) {
+            errors.push(ConfigError::Duplicate { key: key.clone() });
+            continue;
+        }
+
+        let entry = &entries[key];
+        let value = entry
+            .meta("env")
+            .and_then(|i| env.get(i))
+            .or_else(|| file.get(key))
+            .map(String::as_str)
+            .or_else(|| entry.meta("default"));
+
+        match value {
+            Some(value) => {
+                if let Err(message) = entry.item().set_str(value) {
+                    errors.push(ConfigError::Invalid {
+                        key: key.clone(),
+                        value: value.to_string(),
+                        message,
+                    });
+                }
+            }
+            None => errors.push(ConfigError::Missing { key: key.clone() }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Sets every key with the `config` label from environment variables `env`
+/// (like `std::env::vars()`) and the TOML file `toml`.
+pub fn load_from(
+    env: impl IntoIterator<Item = (String, String)>,
+    toml: &str,
+) -> Result<(), Vec<ConfigError>> {
+    load_entries(iter_entries(), env, toml)
+}
+
+/// Lists every key with the `config` label, with its type, environment variable, default and doc comment.
+pub fn describe() -> String {
+    let mut entries: Vec<_> = iter_entries().map(|i| (key(&i), i)).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut res = String::new();
+    for (key, entry) in entries {
+        let _ = write!(res, "{}: {}", key, entry.item().type_name());
+        if let Some(env) = entry.meta("env") {
+            let _ = write!(res, " (env {})", env);
+        }
+        if let Some(default) = entry.meta("default") {
+            let _ = write!(res, " (default {})", default);
+        }
+        res.push('\n');
+
+        for line in entry.doc().lines() {
+            let _ = writeln!(res, "    {}", line);
+        }
+    }
+
+    res
+}
@@ -0,0 +1,141 @@
+use std::fmt;
+
+/// Where an item was labeled in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location {
+    file: &'static str,
+    line: u32,
+    column: u32,
+}
+
+impl Location {
+    #[doc(hidden)]
+    // DO NOT USE DIRECTLY! Locations are created when items are labeled.
+    pub const fn __new(file: &'static str, line: u32, column: u32) -> Self {
+        Self { file, line, column }
+    }
+
+    pub fn file(&self) -> &'static str {
+        self.file
+    }
+
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// The visibility a labeled item was declared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Visibility {
+    /// `pub`
+    Public,
+    /// `pub(crate)`
+    Crate,
+    /// `pub(super)`
+    Super,
+    /// `pub(in path)`, with the path as it was written.
+    Restricted(&'static str),
+    /// No visibility (or `pub(self)`), so the item is private to its module.
+    Private,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "pub"),
+            Visibility::Crate => write!(f, "pub(crate)"),
+            Visibility::Super => write!(f, "pub(super)"),
+            Visibility::Restricted(path) => write!(f, "pub(in {})", path),
+            Visibility::Private => write!(f, "private"),
+        }
+    }
+}
+
+/// A labeled item, together with what is known about it.
+///
+/// Entries are returned by the `iter_entries()` function of every label.
+#[derive(Debug, Clone, Copy)]
+pub struct Entry<T> {
+    name: &'static str,
+    doc: &'static str,
+    location: Location,
+    visibility: Visibility,
+    metadata: &'static [(&'static str, &'static str)],
+    item: T,
+}
+
+impl<T: Copy> Entry<T> {
+    #[doc(hidden)]
+    // DO NOT USE DIRECTLY! Entries are created when items are labeled.
+    pub const fn __new(
+        name: &'static str,
+        doc: &'static str,
+        location: Location,
+        visibility: Visibility,
+        metadata: &'static [(&'static str, &'static str)],
+        item: T,
+    ) -> Self {
+        Self {
+            name,
+            doc,
+            location,
+            visibility,
+            metadata,
+            item,
+        }
+    }
+
+    /// The name of the labeled item.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The doc comment of the labeled item, or an empty string if it has none.
+    ///
+    /// All lines of the doc comment are joined with newlines, and the indentation
+    /// common to all lines is removed.
+    pub fn doc(&self) -> &'static str {
+        self.doc
+    }
+
+    /// Where the item was labeled.
+    pub fn location(&self) -> Location {
+        self.location
+    }
+
+    /// The visibility the item was declared with.
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    /// The arguments given to the label attribute, as `(key, value)` pairs in the order they were written.
+    ///
+    /// `#[x::label(ignore, method = "GET", retries = 3)]` results in
+    /// `[("ignore", ""), ("method", "GET"), ("retries", "3")]`.
+    pub fn metadata(&self) -> &'static [(&'static str, &'static str)] {
+        self.metadata
+    }
+
+    /// The value of the first argument named `key` given to the label attribute.
+    pub fn meta(&self, key: &str) -> Option<&'static str> {
+        self.metadata
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| *v)
+    }
+
+    /// The labeled item itself, as returned by `iter()`.
+    pub fn item(&self) -> T {
+        self.item
+    }
+}
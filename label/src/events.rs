@@ -0,0 +1,181 @@
+//! Support for event labels, where every function with the label subscribes to an event.
+//!
+//! Event labels are created with `create_label!`:
+//!
+//! ```
+//! use label::create_label;
+//! use label::events::Propagation;
+//!
+//! pub struct UserCreated {
+//!     pub name: String,
+//! }
+//!
+//! create_label!(
+//!     event on_user_created(UserCreated);
+//!     event on_user_deleted(UserCreated) -> Result<Propagation, String>;
+//! );
+//!
+//! #[on_user_created::label]
+//! fn send_welcome_mail(event: &UserCreated) {
+//!     println!("Welcome, {}!", event.name);
+//! }
+//!
+//! #[on_user_deleted::label(priority = 10)]
+//! fn check_permissions(event: &UserCreated) -> Result<Propagation, String> {
+//!     if event.name == "admin" {
+//!         Err("can't delete the admin".to_string())
+//!     } else {
+//!         Ok(Propagation::Continue)
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let emitted = on_user_created::emit(&UserCreated { name: "jonay".to_string() });
+//!     assert_eq!(emitted.called, vec!["send_welcome_mail"]);
+//!
+//!     let emitted = on_user_deleted::emit(&UserCreated { name: "admin".to_string() });
+//!     assert!(!emitted.is_ok());
+//! }
+//! ```
+//!
+//! `emit` calls the subscribers from the highest to the lowest `priority` argument, an integer
+//! which defaults to 0.
+//! Subscribers with the same priority are called in the order in which they were registered.
+//!
+//! Subscribers can return anything implementing [`Subscriber`]: `()`, a [`Propagation`]
+//! to stop calling the remaining subscribers, or a `Result` of either of those.
+//! `emit` stops at the first error, while `emit_collect` keeps calling
+//! subscribers and collects all errors.
+
+use crate::Entry;
+use std::convert::Infallible;
+
+/// Whether the remaining subscribers of an event should be called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Propagation {
+    Continue,
+    Stop,
+}
+
+/// The return value of a subscriber.
+pub trait Subscriber {
+    type Error;
+
+    fn into_result(self) -> Result<Propagation, Self::Error>;
+}
+
+impl Subscriber for () {
+    type Error = Infallible;
+
+    fn into_result(self) -> Result<Propagation, Self::Error> {
+        Ok(Propagation::Continue)
+    }
+}
+
+impl Subscriber for Propagation {
+    type Error = Infallible;
+
+    fn into_result(self) -> Result<Propagation, Self::Error> {
+        Ok(self)
+    }
+}
+
+impl<E> Subscriber for Result<(), E> {
+    type Error = E;
+
+    fn into_result(self) -> Result<Propagation, Self::Error> {
+        self.map(|_| Propagation::Continue)
+    }
+}
+
+impl<E> Subscriber for Result<Propagation, E> {
+    type Error = E;
+
+    fn into_result(self) -> Result<Propagation, Self::Error> {
+        self
+    }
+}
+
+/// What happened when an event was emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emitted<E> {
+    /// The names of the subscribers which were called, in order.
+    pub called: Vec<&'static str>,
+    /// The subscriber which returned [`Propagation::Stop`], if any.
+    pub stopped_by: Option<&'static str>,
+    /// The subscribers which returned an error, with the error.
+    pub errors: Vec<(&'static str, E)>,
+}
+
+impl<E> Emitted<E> {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), Vec<(&'static str, E)>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// The priority of a subscriber.
+///
+/// # Panics
+///
+/// When the `priority` argument of the subscriber's label is not an integer.
+pub fn priority<H: Copy>(entry: &Entry<H>) -> i64 {
+    match entry.meta("priority") {
+        None => 0,
+        Some(i) => i.parse().unwrap_or_else(|_| {
+            panic!(
+                "priority of subscriber `{}` must be an integer, not `{}`",
+                entry.name(),
+                i
+            )
+        }),
+    }
+}
+
+#[doc(hidden)]
+// Used by the `emit` and `emit_collect` functions generated for event labels.
+pub fn __emit<T: ?Sized, H, R>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+    event: &T,
+    collect_errors: bool,
+) -> Emitted<R::Error>
+where
+    H: Copy + Fn(&T) -> R,
+    R: Subscriber,
+{
+    let mut entries: Vec<_> = entries.into_iter().collect();
+    // stable, so entries with the same priority keep their order.
+    entries.sort_by_key(|i| std::cmp::Reverse(priority(i)));
+
+    let mut res = Emitted {
+        called: Vec::new(),
+        stopped_by: None,
+        errors: Vec::new(),
+    };
+
+    for entry in entries {
+        res.called.push(entry.name());
+        match (entry.item())(event).into_result() {
+            Ok(Propagation::Continue) => {}
+            Ok(Propagation::Stop) => {
+                res.stopped_by = Some(entry.name());
+                break;
+            }
+            Err(e) => {
+                res.errors.push((entry.name(), e));
+                if !collect_errors {
+                    break;
+                }
+            }
+        }
+    }
+
+    res
+}
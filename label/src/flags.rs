@@ -0,0 +1,186 @@
+//! Feature flags, where every flag is a labeled static.
+//!
+//! Flags can be turned on and off at runtime, by name or from a file:
+//!
+//! ```
+//! use label::flags::{self, Flag};
+//!
+//! /// Shows the new user interface.
+//! #[flags::label]
+//! static NEW_UI: Flag = Flag::off();
+//!
+//! #[flags::label]
+//! static FAST_PATH: Flag = Flag::on();
+//!
+//! fn main() {
+//!     flags::set("NEW_UI", true).unwrap();
+//!     assert!(NEW_UI.is_enabled());
+//!
+//!     flags::load_from_str("FAST_PATH = off").unwrap();
+//!     assert!(!FAST_PATH.is_enabled());
+//!
+//!     for (name, enabled) in flags::list() {
+//!         println!("{}: {}", name, enabled);
+//!     }
+//! }
+//! ```
+//!
+//! Flags are named after their static. In files, every line sets one flag with `NAME = value`,
+//! where the value is `true`, `false`, `on` or `off`. Everything after a `#` is a comment.
+
+use crate::create_label;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A feature flag, which is either enabled or disabled.
+#[derive(Debug, Default)]
+pub struct Flag {
+    enabled: AtomicBool,
+}
+
+impl Flag {
+    /// A flag which is disabled until it is set.
+    pub const fn off() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+        }
+    }
+
+    /// A flag which is enabled until it is set.
+    pub const fn on() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+mod registry {
+    use super::*;
+
+    create_label!(
+        pub static flags: Flag;
+    );
+}
+
+pub use self::registry::flags::{add, iter, iter_entries, iter_named, label};
+
+/// A problem found while setting flags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlagError {
+    /// There is no flag with this name. `line` is the line in the file it was set on, if any.
+    Unknown { name: String, line: Option<usize> },
+    /// A line of a file which is not `NAME = value` with a valid value.
+    Syntax { line: usize, message: String },
+}
+
+impl fmt::Display for FlagError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FlagError::Unknown { name, line: None } => write!(f, "unknown flag `{}`", name),
+            FlagError::Unknown {
+                name,
+                line: Some(line),
+            } => write!(f, "line {}: unknown flag `{}`", line, name),
+            FlagError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error for FlagError {}
+
+/// Enables or disables every flag named `name`.
+pub fn set(name: &str, enabled: bool) -> Result<(), FlagError> {
+    let mut found = false;
+    for (_, flag) in iter_named().filter(|(i, _)| *i == name) {
+        flag.set(enabled);
+        found = true;
+    }
+
+    if found {
+        Ok(())
+    } else {
+        Err(FlagError::Unknown {
+            name: name.to_string(),
+            line: None,
+        })
+    }
+}
+
+fn parse_value(value: &str) -> Option<bool> {
+    match value {
+        "true" | "on" => Some(true),
+        "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Sets flags from the lines of a file, like `NEW_UI = on`.
+///
+/// Valid lines are applied, even when other lines have problems.
+pub fn load_from_str(s: &str) -> Result<(), Vec<FlagError>> {
+    let mut errors = Vec::new();
+
+    for (index, line) in s.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (name, value) = match line.split_once('=') {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => {
+                errors.push(FlagError::Syntax {
+                    line: line_number,
+                    message: "expected `NAME = value`".to_string(),
+                });
+                continue;
+            }
+        };
+
+        let enabled = match parse_value(value) {
+            Some(i) => i,
+            None => {
+                errors.push(FlagError::Syntax {
+                    line: line_number,
+                    message: format!(
+                        "expected `true`, `false`, `on` or `off` as value of `{}`, not `{}`",
+                        name, value
+                    ),
+                });
+                continue;
+            }
+        };
+
+        if set(name, enabled).is_err() {
+            errors.push(FlagError::Unknown {
+                name: name.to_string(),
+                line: Some(line_number),
+            });
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The name of every flag and whether it is enabled, ordered by name.
+pub fn list() -> Vec<(&'static str, bool)> {
+    let mut res: Vec<_> = iter_named()
+        .map(|(name, flag)| (name, flag.is_enabled()))
+        .collect();
+    res.sort();
+    res
+}
@@ -0,0 +1,567 @@
+//! A test runner for `harness = false` test targets, which runs every function with a label.
+//!
+//! Add a test target without the default test harness to your `Cargo.toml`:
+//!
+//! ```toml
+//! [[test]]
+//! name = "checks"
+//! harness = false
+//! ```
+//!
+//! And hand the functions of a label to [`main`] in `tests/checks.rs`:
+//!
+//! ```no_run
+//! use label::create_label;
+//!
+//! create_label!(fn check() -> Result<(), String>);
+//!
+//! #[check::label]
+//! fn addition() -> Result<(), String> {
+//!     if 1 + 1 == 2 {
+//!         Ok(())
+//!     } else {
+//!         Err("math is broken".to_string())
+//!     }
+//! }
+//!
+//! fn main() {
+//!     label::harness::main(check::iter_named());
+//! }
+//! ```
+//!
+//! The runner understands the most common arguments of the default test harness
+//! (name filters, `--exact`, `--skip`, `--list`, `--ignored`, `--include-ignored`,
+//! `--test-threads` and `--format pretty|terse|json`) and produces the same output.
+//! Tests run in parallel, and a test that panics only fails that test.
+//!
+//! Functions returning `()` can be used too, these fail only when they panic.
+//! When passing `iter_entries()` to the runner, tests are ignored by giving the label an `ignore` argument:
+//!
+//! ```ignore
+//! #[check::label(ignore)]
+//! fn expensive() -> Result<(), String> {
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Otherwise, tests can be marked as ignored with [`Test::with_ignored`].
+
+use crate::catch::catch;
+use crate::json;
+use crate::Entry;
+use std::fmt::Debug;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+type TestFn = Box<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+/// A single test, usually created from an item of a label's `iter_named()`.
+pub struct Test {
+    name: String,
+    func: TestFn,
+    ignored: bool,
+}
+
+impl Test {
+    /// Creates a test which fails when `func` returns an error or panics.
+    pub fn new<E: Debug + 'static>(name: impl Into<String>, func: fn() -> Result<(), E>) -> Self {
+        Self {
+            name: name.into(),
+            func: Box::new(move || func().map_err(|e| format!("Error: {:?}", e))),
+            ignored: false,
+        }
+    }
+
+    /// Ignored tests only run with `--ignored` or `--include-ignored`.
+    pub fn with_ignored(mut self, ignored: bool) -> Self {
+        self.ignored = ignored;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_ignored(&self) -> bool {
+        self.ignored
+    }
+}
+
+impl<E: Debug + 'static> From<(&'static str, fn() -> Result<(), E>)> for Test {
+    fn from((name, func): (&'static str, fn() -> Result<(), E>)) -> Self {
+        Test::new(name, func)
+    }
+}
+
+impl From<(&'static str, fn())> for Test {
+    fn from((name, func): (&'static str, fn())) -> Self {
+        Self {
+            name: name.to_string(),
+            func: Box::new(move || {
+                func();
+                Ok(())
+            }),
+            ignored: false,
+        }
+    }
+}
+
+/// Tests created from entries are ignored when the label has an `ignore` argument.
+impl<E: Debug + 'static> From<Entry<fn() -> Result<(), E>>> for Test {
+    fn from(entry: Entry<fn() -> Result<(), E>>) -> Self {
+        Test::new(entry.name(), entry.item()).with_ignored(entry.meta("ignore").is_some())
+    }
+}
+
+/// Tests created from entries are ignored when the label has an `ignore` argument.
+impl From<Entry<fn()>> for Test {
+    fn from(entry: Entry<fn()>) -> Self {
+        Test::from((entry.name(), entry.item())).with_ignored(entry.meta("ignore").is_some())
+    }
+}
+
+/// The output format of the runner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// One line per test.
+    #[default]
+    Pretty,
+    /// One character per test.
+    Terse,
+    /// One json object per line, like `--format json` of the default test harness.
+    Json,
+}
+
+/// Command line arguments of the runner.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Arguments {
+    /// Only run tests whose name contains one of these.
+    pub filters: Vec<String>,
+    /// Don't run tests whose name contains one of these.
+    pub skip: Vec<String>,
+    /// Filters have to match the test name exactly.
+    pub exact: bool,
+    /// List the tests instead of running them.
+    pub list: bool,
+    /// Only run ignored tests.
+    pub ignored: bool,
+    /// Run ignored tests along with the others.
+    pub include_ignored: bool,
+    pub format: Format,
+    /// Number of tests to run at the same time. Defaults to `RUST_TEST_THREADS`,
+    /// or the available parallelism.
+    pub test_threads: Option<usize>,
+}
+
+impl Arguments {
+    /// Parses the arguments the program was started with.
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    /// Parses arguments, not including the program name.
+    pub fn parse<I, S>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut res = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with('-') {
+                res.filters.push(arg);
+                continue;
+            }
+
+            let (flag, inline_value) = match arg.find('=') {
+                Some(i) => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+                None => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| format!("argument to option '{}' missing", flag))
+            };
+
+            match flag.as_str() {
+                "--exact" => res.exact = true,
+                "--list" => res.list = true,
+                "--ignored" => res.ignored = true,
+                "--include-ignored" => res.include_ignored = true,
+                "-q" | "--quiet" => res.format = Format::Terse,
+                "--skip" => res.skip.push(value()?),
+                "--format" => {
+                    res.format = match value()?.as_str() {
+                        "pretty" => Format::Pretty,
+                        "terse" => Format::Terse,
+                        "json" => Format::Json,
+                        other => {
+                            return Err(format!(
+                                "argument for --format must be pretty, terse, or json (was {})",
+                                other
+                            ))
+                        }
+                    }
+                }
+                "--test-threads" => {
+                    let threads = value()?;
+                    match threads.parse() {
+                        Ok(i) if i > 0 => res.test_threads = Some(i),
+                        _ => {
+                            return Err(format!(
+                                "argument for --test-threads must be a number > 0 (was {})",
+                                threads
+                            ))
+                        }
+                    }
+                }
+                // accepted for compatibility with the default test harness, but they don't change anything.
+                "--nocapture" | "--show-output" | "--test" | "--bench" => {}
+                "--color" | "-Z" => {
+                    value()?;
+                }
+                _ if flag.starts_with("-Z") => {}
+                _ => return Err(format!("unrecognized option: '{}'", flag)),
+            }
+        }
+
+        Ok(res)
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        let matches = |filter: &String| {
+            if self.exact {
+                name == filter
+            } else {
+                name.contains(filter.as_str())
+            }
+        };
+
+        (self.filters.is_empty() || self.filters.iter().any(matches))
+            && !self.skip.iter().any(matches)
+    }
+}
+
+/// The results of a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Conclusion {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub filtered_out: usize,
+}
+
+impl Conclusion {
+    pub fn has_failed(&self) -> bool {
+        self.failed > 0
+    }
+}
+
+enum Outcome {
+    Ok,
+    Failed(String),
+    Ignored,
+}
+
+struct Printer<W> {
+    out: W,
+    format: Format,
+}
+
+impl<W: Write> Printer<W> {
+    fn list(&mut self, tests: &[&Test]) -> io::Result<()> {
+        match self.format {
+            Format::Pretty | Format::Terse => {
+                for test in tests {
+                    writeln!(self.out, "{}: test", test.name)?;
+                }
+                if self.format == Format::Pretty {
+                    writeln!(self.out)?;
+                    writeln!(self.out, "{} tests, 0 benchmarks", tests.len())?;
+                }
+            }
+            Format::Json => {
+                writeln!(self.out, r#"{{ "type": "suite", "event": "discovery" }}"#)?;
+                for test in tests {
+                    writeln!(
+                        self.out,
+                        r#"{{ "type": "test", "event": "discovered", "name": {}, "ignore": {} }}"#,
+                        json::string(&test.name),
+                        test.ignored
+                    )?;
+                }
+                writeln!(
+                    self.out,
+                    r#"{{ "type": "suite", "event": "completed", "tests": {}, "benchmarks": 0, "total": {}, "ignored": {} }}"#,
+                    tests.len(),
+                    tests.len(),
+                    tests.iter().filter(|i| i.ignored).count()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn start(&mut self, count: usize) -> io::Result<()> {
+        match self.format {
+            Format::Pretty | Format::Terse => {
+                let plural = if count == 1 { "" } else { "s" };
+                writeln!(self.out)?;
+                writeln!(self.out, "running {} test{}", count, plural)
+            }
+            Format::Json => writeln!(
+                self.out,
+                r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#,
+                count
+            ),
+        }
+    }
+
+    fn started(&mut self, name: &str) -> io::Result<()> {
+        if self.format == Format::Json {
+            writeln!(
+                self.out,
+                r#"{{ "type": "test", "event": "started", "name": {} }}"#,
+                json::string(name)
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn result(&mut self, name: &str, outcome: &Outcome) -> io::Result<()> {
+        match (self.format, outcome) {
+            (Format::Pretty, Outcome::Ok) => writeln!(self.out, "test {} ... ok", name)?,
+            (Format::Pretty, Outcome::Failed(_)) => writeln!(self.out, "test {} ... FAILED", name)?,
+            (Format::Pretty, Outcome::Ignored) => writeln!(self.out, "test {} ... ignored", name)?,
+            (Format::Terse, Outcome::Ok) => write!(self.out, ".")?,
+            (Format::Terse, Outcome::Failed(_)) => write!(self.out, "F")?,
+            (Format::Terse, Outcome::Ignored) => write!(self.out, "i")?,
+            (Format::Json, Outcome::Ok) => writeln!(
+                self.out,
+                r#"{{ "type": "test", "name": {}, "event": "ok" }}"#,
+                json::string(name)
+            )?,
+            (Format::Json, Outcome::Failed(stdout)) => writeln!(
+                self.out,
+                r#"{{ "type": "test", "name": {}, "event": "failed", "stdout": {} }}"#,
+                json::string(name),
+                json::string(stdout)
+            )?,
+            (Format::Json, Outcome::Ignored) => writeln!(
+                self.out,
+                r#"{{ "type": "test", "name": {}, "event": "ignored" }}"#,
+                json::string(name)
+            )?,
+        }
+
+        self.out.flush()
+    }
+
+    fn finish(
+        &mut self,
+        conclusion: &Conclusion,
+        failures: &[(&str, String)],
+        elapsed: Duration,
+    ) -> io::Result<()> {
+        let status = if conclusion.has_failed() {
+            "failed"
+        } else {
+            "ok"
+        };
+
+        if self.format == Format::Json {
+            return writeln!(
+                self.out,
+                r#"{{ "type": "suite", "event": "{}", "passed": {}, "failed": {}, "ignored": {}, "measured": 0, "filtered_out": {}, "exec_time": {} }}"#,
+                status,
+                conclusion.passed,
+                conclusion.failed,
+                conclusion.ignored,
+                conclusion.filtered_out,
+                elapsed.as_secs_f64()
+            );
+        }
+
+        if self.format == Format::Terse {
+            writeln!(self.out)?;
+        }
+
+        if !failures.is_empty() {
+            writeln!(self.out)?;
+            writeln!(self.out, "failures:")?;
+            writeln!(self.out)?;
+            for (name, stdout) in failures {
+                writeln!(self.out, "---- {} stdout ----", name)?;
+                writeln!(self.out, "{}", stdout)?;
+            }
+            writeln!(self.out)?;
+            writeln!(self.out, "failures:")?;
+            for (name, _) in failures {
+                writeln!(self.out, "    {}", name)?;
+            }
+        }
+
+        writeln!(self.out)?;
+        writeln!(
+            self.out,
+            "test result: {}. {} passed; {} failed; {} ignored; 0 measured; {} filtered out; finished in {:.2}s",
+            if conclusion.has_failed() { "FAILED" } else { "ok" },
+            conclusion.passed,
+            conclusion.failed,
+            conclusion.ignored,
+            conclusion.filtered_out,
+            elapsed.as_secs_f64()
+        )?;
+        writeln!(self.out)
+    }
+}
+
+fn run_test(test: &Test) -> Outcome {
+    match catch(|| (test.func)()) {
+        Ok(Ok(())) => Outcome::Ok,
+        Ok(Err(e)) => Outcome::Failed(e),
+        Err(panic) => Outcome::Failed(match panic.location {
+            Some(location) => format!(
+                "thread '{}' panicked at {}:\n{}",
+                test.name, location, panic.message
+            ),
+            None => format!("thread '{}' panicked:\n{}", test.name, panic.message),
+        }),
+    }
+}
+
+fn thread_count(args: &Arguments) -> usize {
+    args.test_threads
+        .or_else(|| std::env::var("RUST_TEST_THREADS").ok()?.parse().ok())
+        .or_else(|| thread::available_parallelism().ok().map(Into::into))
+        .unwrap_or(1)
+}
+
+/// Runs `tests` according to `args`, writing the results to `out`.
+///
+/// Tests are ordered by name. Only an error writing to `out` makes this return an error,
+/// failing tests are reported in the [`Conclusion`].
+pub fn run<T, W>(
+    args: &Arguments,
+    tests: impl IntoIterator<Item = T>,
+    out: W,
+) -> io::Result<Conclusion>
+where
+    T: Into<Test>,
+    W: Write,
+{
+    let mut tests: Vec<Test> = tests.into_iter().map(Into::into).collect();
+    tests.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let total = tests.len();
+    let selected: Vec<&Test> = tests
+        .iter()
+        .filter(|i| args.matches(&i.name))
+        .filter(|i| !args.ignored || i.ignored)
+        .collect();
+
+    let mut printer = Printer {
+        out,
+        format: args.format,
+    };
+    let mut conclusion = Conclusion {
+        filtered_out: total - selected.len(),
+        ..Conclusion::default()
+    };
+
+    if args.list {
+        printer.list(&selected)?;
+        return Ok(conclusion);
+    }
+
+    let start = Instant::now();
+    printer.start(selected.len())?;
+
+    let (ignored, pending): (Vec<&Test>, Vec<&Test>) = selected
+        .into_iter()
+        .partition(|i| i.ignored && !args.ignored && !args.include_ignored);
+
+    for test in ignored {
+        printer.started(&test.name)?;
+        printer.result(&test.name, &Outcome::Ignored)?;
+        conclusion.ignored += 1;
+    }
+
+    let mut failures = Vec::new();
+    let threads = thread_count(args).min(pending.len()).max(1);
+    let next = AtomicUsize::new(0);
+
+    thread::scope(|s| -> io::Result<()> {
+        let (sender, receiver) = mpsc::channel();
+
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let (pending, next) = (&pending, &next);
+            s.spawn(move || {
+                while let Some(test) = pending.get(next.fetch_add(1, Ordering::SeqCst)) {
+                    if sender.send((test, None)).is_err()
+                        || sender.send((test, Some(run_test(test)))).is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        for (test, outcome) in receiver {
+            let outcome = match outcome {
+                Some(i) => i,
+                None => {
+                    printer.started(&test.name)?;
+                    continue;
+                }
+            };
+
+            printer.result(&test.name, &outcome)?;
+            match outcome {
+                Outcome::Ok => conclusion.passed += 1,
+                Outcome::Failed(stdout) => {
+                    conclusion.failed += 1;
+                    failures.push((test.name.as_str(), stdout));
+                }
+                Outcome::Ignored => conclusion.ignored += 1,
+            }
+        }
+
+        Ok(())
+    })?;
+
+    failures.sort_by(|a, b| a.0.cmp(b.0));
+    printer.finish(&conclusion, &failures, start.elapsed())?;
+
+    Ok(conclusion)
+}
+
+/// Parses the command line arguments, runs `tests` and exits the process,
+/// with exit code 101 if any test failed like the default test harness.
+pub fn main<T: Into<Test>>(tests: impl IntoIterator<Item = T>) -> ! {
+    let args = match Arguments::from_args() {
+        Ok(i) => i,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(101);
+        }
+    };
+
+    match run(&args, tests, io::stdout()) {
+        Ok(conclusion) if !conclusion.has_failed() => std::process::exit(0),
+        Ok(_) => std::process::exit(101),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(101);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Initialization hooks, which run in an order based on their dependencies.
+//!
+//! Hooks are functions labeled with `init::label`. They can name other hooks they
+//! should run `after` or `before`:
+//!
+//! ```
+//! use label::init;
+//!
+//! #[init::label]
+//! fn db() {
+//!     println!("connecting to the database");
+//! }
+//!
+//! #[init::label(after = "db", before = "http")]
+//! fn cache() {
+//!     println!("filling the cache");
+//! }
+//!
+//! #[init::label(after = "db")]
+//! fn http() {
+//!     println!("starting the http server");
+//! }
+//!
+//! fn main() {
+//!     init::run_all().unwrap();
+//! }
+//! ```
+//!
+//! Multiple hooks can be given by repeating the argument, or by separating them with commas:
+//! `#[init::label(after = "db, config")]`. Hooks which don't depend on each other run in
+//! alphabetical order.
+//!
+//! The same ordering can be applied to the entries of any other label with [`sort`].
+
+use crate::create_label;
+use crate::{Entry, Location};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+type Hooks = Vec<Entry<fn()>>;
+
+mod registry {
+    use super::*;
+
+    create_label!(
+        pub fn hooks() -> ();
+    );
+}
+
+pub use self::registry::hooks::{add, iter, iter_entries, iter_named, label};
+
+/// A hook mentioned in an [`InitError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hook {
+    pub name: &'static str,
+    pub location: Location,
+}
+
+impl<H: Copy> From<&Entry<H>> for Hook {
+    fn from(entry: &Entry<H>) -> Self {
+        Self {
+            name: entry.name(),
+            location: entry.location(),
+        }
+    }
+}
+
+impl fmt::Display for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` (at {})", self.name, self.location)
+    }
+}
+
+/// Why hooks could not be ordered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InitError {
+    /// A hook should run before or after a hook which doesn't exist.
+    MissingDependency {
+        hook: Hook,
+        dependency: &'static str,
+    },
+    /// Hooks which (indirectly) should run after themselves. Every hook should run after the previous one,
+    /// and the first hook after the last one.
+    Cycle(Vec<Hook>),
+    /// Two hooks with the same name, so it is unclear which is meant by `before` and `after`.
+    DuplicateName(Hook, Hook),
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::MissingDependency { hook, dependency } => write!(
+                f,
+                "hook {} depends on `{}`, which doesn't exist",
+                hook, dependency
+            ),
+            InitError::Cycle(hooks) => {
+                write!(f, "hooks depend on each other in a cycle: ")?;
+                for hook in hooks {
+                    write!(f, "{} -> ", hook)?;
+                }
+                write!(f, "`{}`", hooks[0].name)
+            }
+            InitError::DuplicateName(a, b) => {
+                write!(f, "two hooks are named `{}`: {} and {}", a.name, a, b)
+            }
+        }
+    }
+}
+
+impl Error for InitError {}
+
+fn dependencies<H: Copy>(entry: &Entry<H>, key: &str) -> Vec<&'static str> {
+    entry
+        .metadata()
+        .iter()
+        .filter(|(k, _)| *k == key)
+        .flat_map(|(_, v)| v.split(','))
+        .map(str::trim)
+        .filter(|i| !i.is_empty())
+        .collect()
+}
+
+/// Orders entries so every entry comes after the entries named by its `after` arguments,
+/// and before the entries named by its `before` arguments.
+pub fn sort<H: Copy>(
+    entries: impl IntoIterator<Item = Entry<H>>,
+) -> Result<Vec<Entry<H>>, InitError> {
+    let entries: Vec<_> = entries.into_iter().collect();
+
+    let mut indices = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(previous) = indices.insert(entry.name(), index) {
+            return Err(InitError::DuplicateName(
+                (&entries[previous]).into(),
+                entry.into(),
+            ));
+        }
+    }
+
+    // predecessors[i] contains the hooks which have to run before hook i.
+    let mut predecessors = vec![BTreeSet::new(); entries.len()];
+    for (index, entry) in entries.iter().enumerate() {
+        for (key, is_after) in [("after", true), ("before", false)] {
+            for dependency in dependencies(entry, key) {
+                let other = *indices
+                    .get(dependency)
+                    .ok_or(InitError::MissingDependency {
+                        hook: entry.into(),
+                        dependency,
+                    })?;
+
+                if is_after {
+                    predecessors[index].insert(other);
+                } else {
+                    predecessors[other].insert(index);
+                }
+            }
+        }
+    }
+
+    let mut remaining: Vec<usize> = predecessors.iter().map(BTreeSet::len).collect();
+    let mut ready: BTreeSet<_> = (0..entries.len())
+        .filter(|i| remaining[*i] == 0)
+        .map(|i| (entries[i].name(), i))
+        .collect();
+    let mut res = Vec::with_capacity(entries.len());
+
+    while let Some((_, index)) = ready.pop_first() {
+        res.push(entries[index]);
+
+        for (other, predecessors) in predecessors.iter().enumerate() {
+            if predecessors.contains(&index) {
+                remaining[other] -= 1;
+                if remaining[other] == 0 {
+                    ready.insert((entries[other].name(), other));
+                }
+            }
+        }
+    }
+
+    if res.len() == entries.len() {
+        return Ok(res);
+    }
+
+    // every hook which didn't run still has a predecessor which didn't run.
+    // Walking back through those predecessors has to end up in a cycle.
+    let mut current = (0..entries.len()).find(|i| remaining[*i] > 0).unwrap();
+    let mut path = Vec::new();
+    while !path.contains(&current) {
+        path.push(current);
+        current = *predecessors[current]
+            .iter()
+            .find(|i| remaining[**i] > 0)
+            .unwrap();
+    }
+
+    let start = path.iter().position(|i| *i == current).unwrap();
+    Err(InitError::Cycle(
+        path[start..]
+            .iter()
+            .rev()
+            .map(|i| (&entries[*i]).into())
+            .collect(),
+    ))
+}
+
+/// The hooks with the `init` label, in the order they will run.
+pub fn order() -> Result<Hooks, InitError> {
+    sort(iter_entries())
+}
+
+/// Runs all hooks with the `init` label. If the hooks can't be ordered,
+/// an error is returned before any hook runs.
+pub fn run_all() -> Result<(), InitError> {
+    for hook in order()? {
+        (hook.item())();
+    }
+
+    Ok(())
+}
@@ -27,10 +27,91 @@
 //!
 //! ```
 //!
-//! Label also supports labels on `static` and `const` variables, and iterating over the names of labeled items.
+//! Label also supports labels on `static` and `const` variables, and iterating over the names and doc comments of labeled items.
 //! For more information about this, visit the docs on [create_label](label_macros::create_label)
 //!
+//! ```
+//! use label::create_label;
+//!
+//! create_label!(
+//!     fn test() -> ();
+//!     fn test1(usize) -> (usize);
+//!     const name: usize;
+//!     static other_name: usize;
+//! );
+//!
+//! #[test1::label]
+//! fn double(x: usize) -> usize {
+//!     x * 2
+//! }
+//!
+//! #[name::label]
+//! const ANSWER: usize = 42;
+//!
+//! fn main() {
+//!     for (function, func) in test1::iter_named() {
+//!         assert_eq!((function, func(2)), ("double", 4));
+//!     }
+//!     assert_eq!(name::iter().collect::<Vec<_>>(), vec![&42]);
+//!     assert_eq!(test::iter().count() + other_name::iter().count(), 0);
+//! }
+//! ```
+//!
+//! Labeled `static mut` variables can be changed without `unsafe` through mutable labels, see [mutable].
+//!
+//! A label can also be made of a trait, to label implementations of the trait, see [registry].
+//!
+//! Every function label can call all of its functions at once, see [calls].
+//!
+//! Labels can also be used as events with subscribers, see [events]. Ready-made labels
+//! for initialization hooks which depend on each other and for teardown hooks are available
+//! in [init] and [teardown]. Labeled statics can be used as metrics, configuration keys or feature flags
+//! with [metrics], [config] and [flags].
+//!
+//! Label also comes with a ready-made test runner, benchmark runner and subcommand dispatcher
+//! for labeled functions, see [harness], [bench] and [cli].
+//!
+//! # Features
+//!
+//! * `rayon`: adds `par_iter()` and `par_iter_named()` to every label, to go through labeled items in parallel.
+//! * `routes`: adds [routes], a router for labeled request handlers.
+//!
+
+mod arguments;
+pub mod bench;
+pub mod calls;
+mod catch;
+pub mod cli;
+pub mod config;
+mod entry;
+pub mod events;
+pub mod flags;
+pub mod harness;
+pub mod init;
+mod json;
+pub mod metrics;
+pub mod mutable;
+#[cfg(feature = "routes")]
+pub mod routes;
+mod signature;
+pub mod teardown;
+mod visibility;
+
+// makes `label::` paths generated by the macros work inside this crate as well.
+extern crate self as label;
 
+#[doc(hidden)]
+pub use arguments::{__Integer, __Unsigned, __declares, __integer, __unsigned};
 pub use ctor::ctor;
+pub use entry::{Entry, Location, Visibility};
 pub use label_macros::__label;
+pub use label_macros::__label_attribute;
 pub use label_macros::create_label;
+pub use label_macros::labeled;
+pub use label_macros::registry;
+#[cfg(feature = "rayon")]
+pub use rayon;
+#[doc(hidden)]
+pub use signature::__Signature;
+#[doc(hidden)]
+pub use visibility::{__Visible, kinds as __visibility};
@@ -0,0 +1,276 @@
+//! A registry of metrics, where every metric is a labeled static.
+//!
+//! Metrics are counters, gauges and histograms, which can be updated from any thread:
+//!
+//! ```
+//! use label::metrics::{self, Counter, Gauge, Histogram};
+//!
+//! /// Requests handled since starting.
+//! #[metrics::label]
+//! static REQUESTS: Counter = Counter::new();
+//!
+//! /// Connections which are currently open.
+//! #[metrics::label(name = "open_connections")]
+//! static CONNECTIONS: Gauge = Gauge::new();
+//!
+//! /// Time taken to handle a request, in seconds.
+//! #[metrics::label]
+//! static LATENCY: Histogram<3> = Histogram::new([0.1, 0.5, 1.0]);
+//!
+//! fn main() {
+//!     REQUESTS.inc();
+//!     CONNECTIONS.set(3.0);
+//!     LATENCY.observe(0.25);
+//!
+//!     print!("{}", metrics::render_prometheus());
+//! }
+//! ```
+//!
+//! [`render_prometheus`] renders every labeled metric in the Prometheus text format. The name of a metric
+//! is the lowercased name of the static, unless a `name` argument is given, and its doc comment is the help text.
+//!
+//! Other types can be labeled as metrics by implementing [`Metric`].
+
+use crate::create_label;
+use crate::Entry;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The type of a metric, as written after `# TYPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+impl Kind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram => "histogram",
+        }
+    }
+}
+
+/// A value which can be labeled as a metric.
+pub trait Metric: Sync {
+    fn kind(&self) -> Kind;
+
+    /// Writes the samples of this metric, named `name`, in the Prometheus text format.
+    /// Every sample is written on its own line, ending in a newline.
+    fn write_samples(&self, name: &str, out: &mut String);
+}
+
+mod registry {
+    use super::*;
+
+    create_label!(
+        pub static metrics: dyn Metric;
+    );
+}
+
+pub use self::registry::metrics::{add, iter, iter_entries, iter_named, label};
+
+/// Formats a sample value like Prometheus does.
+fn value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Adds to an `f64` stored as bits in an `AtomicU64`.
+fn add_f64(atomic: &AtomicU64, value: f64) {
+    let _ = atomic.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |i| {
+        Some((f64::from_bits(i) + value).to_bits())
+    });
+}
+
+/// A value which only goes up, like the number of handled requests.
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self {
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    pub fn inc_by(&self, amount: u64) {
+        self.value.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Counter {
+    fn kind(&self) -> Kind {
+        Kind::Counter
+    }
+
+    fn write_samples(&self, name: &str, out: &mut String) {
+        let _ = writeln!(out, "{} {}", name, self.get());
+    }
+}
+
+/// A value which can go up and down, like the number of open connections.
+#[derive(Debug, Default)]
+pub struct Gauge {
+    // the bits of an f64, 0 is 0.0.
+    value: AtomicU64,
+}
+
+impl Gauge {
+    pub const fn new() -> Self {
+        Self {
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set(&self, value: f64) {
+        self.value.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn add(&self, amount: f64) {
+        add_f64(&self.value, amount);
+    }
+
+    pub fn inc(&self) {
+        self.add(1.0);
+    }
+
+    pub fn dec(&self) {
+        self.add(-1.0);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.value.load(Ordering::Relaxed))
+    }
+}
+
+impl Metric for Gauge {
+    fn kind(&self) -> Kind {
+        Kind::Gauge
+    }
+
+    fn write_samples(&self, name: &str, out: &mut String) {
+        let _ = writeln!(out, "{} {}", name, value(self.get()));
+    }
+}
+
+/// The buckets used by most Prometheus clients, meant for durations in seconds.
+pub const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Counts observed values in `N` buckets, like request durations.
+///
+/// Every bucket counts the values less than or equal to its upper bound, which should be given in increasing order.
+/// Values larger than the last bound are only counted in the total.
+#[derive(Debug)]
+pub struct Histogram<const N: usize> {
+    bounds: [f64; N],
+    // the number of values in every bucket, and not in any earlier bucket.
+    buckets: [AtomicU64; N],
+    sum: AtomicU64,
+    count: AtomicU64,
+}
+
+impl<const N: usize> Histogram<N> {
+    pub const fn new(bounds: [f64; N]) -> Self {
+        Self {
+            bounds,
+            buckets: [const { AtomicU64::new(0) }; N],
+            sum: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        if let Some(bucket) = self.bounds.iter().position(|i| value <= *i) {
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        }
+        add_f64(&self.sum, value);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The upper bound of every bucket, with the number of observed values less than or equal to it.
+    pub fn buckets(&self) -> [(f64, u64); N] {
+        let mut total = 0;
+        std::array::from_fn(|i| {
+            total += self.buckets[i].load(Ordering::Relaxed);
+            (self.bounds[i], total)
+        })
+    }
+
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum.load(Ordering::Relaxed))
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+impl<const N: usize> Metric for Histogram<N> {
+    fn kind(&self) -> Kind {
+        Kind::Histogram
+    }
+
+    fn write_samples(&self, name: &str, out: &mut String) {
+        let count = self.count();
+        for (bound, values) in self.buckets() {
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, value(bound), values);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, count);
+        let _ = writeln!(out, "{}_sum {}", name, value(self.sum()));
+        let _ = writeln!(out, "{}_count {}", name, count);
+    }
+}
+
+/// The name of a labeled metric: its `name` argument, or the lowercased name of the static.
+pub fn name(entry: &Entry<&'static dyn Metric>) -> String {
+    match entry.meta("name") {
+        Some(name) => name.to_string(),
+        None => entry.name().to_lowercase(),
+    }
+}
+
+/// Renders metrics in the Prometheus text format, ordered by name.
+pub fn render(entries: impl IntoIterator<Item = Entry<&'static dyn Metric>>) -> String {
+    let mut metrics: Vec<_> = entries.into_iter().map(|i| (name(&i), i)).collect();
+    metrics.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut res = String::new();
+    for (name, entry) in metrics {
+        if !entry.doc().is_empty() {
+            let help = entry.doc().replace('\\', "\\\\").replace('\n', "\\n");
+            let _ = writeln!(res, "# HELP {} {}", name, help);
+        }
+        let _ = writeln!(res, "# TYPE {} {}", name, entry.item().kind().as_str());
+        entry.item().write_samples(&name, &mut res);
+    }
+
+    res
+}
+
+/// Renders every metric with the `metrics` label in the Prometheus text format.
+pub fn render_prometheus() -> String {
+    render(iter_entries())
+}
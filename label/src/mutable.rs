@@ -0,0 +1,150 @@
+//! Support for mutable labels, whose `static mut` items can be changed without `unsafe`.
+//!
+//! A label is mutable when it is declared as `static mut` in `create_label!`:
+//!
+//! ```
+//! use label::create_label;
+//!
+//! create_label!(
+//!     static mut counters: usize;
+//! );
+//!
+//! #[counters::label]
+//! static mut REQUESTS: usize = 0;
+//!
+//! #[counters::label]
+//! static mut ERRORS: usize = 0;
+//!
+//! fn main() {
+//!     for mut counter in counters::iter_mut() {
+//!         *counter += 1;
+//!     }
+//!
+//!     for (name, counter) in counters::iter_named() {
+//!         assert_eq!(*counter.read(), 1, "{}", name);
+//!     }
+//! }
+//! ```
+//!
+//! Mutable labels give out [`StaticMut`] handles instead of references. Every labeled item has
+//! its own lock, which is taken by [`StaticMut::read`] and [`StaticMut::write`], so items can be
+//! read and changed from multiple threads. `iter_mut()` locks items one at a time, as the iterator
+//! reaches them.
+//!
+//! The lock is only used when going through the label. Changing the item directly
+//! (which requires `unsafe`) while a guard exists is undefined behaviour.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+// The lock of every labeled item by its address, so items with multiple labels share a lock.
+static LOCKS: Mutex<BTreeMap<usize, &'static RwLock<()>>> = Mutex::new(BTreeMap::new());
+
+/// A labeled `static mut` item, which can be locked for reading or writing.
+pub struct StaticMut<T: 'static> {
+    item: *mut T,
+    lock: &'static RwLock<()>,
+}
+
+// Safety: access to the item is synchronized by its lock, like an `RwLock<T>`.
+unsafe impl<T: Send + Sync> Send for StaticMut<T> {}
+unsafe impl<T: Send + Sync> Sync for StaticMut<T> {}
+
+impl<T> Clone for StaticMut<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for StaticMut<T> {}
+
+impl<T> fmt::Debug for StaticMut<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticMut")
+            .field("item", &self.item)
+            .finish()
+    }
+}
+
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Item}` is a `static mut`, which can't be labeled with `{Label}`",
+    label = "only mutable labels can label a `static mut`",
+    note = "mutable labels are declared as `static mut` in `create_label!`"
+)]
+// Checks that `static mut` items are labeled with mutable labels, which lock them. `Item` and
+// `Label` are types named after the item and the label, like in `__Visible`.
+pub trait __Mutable<Item, Label> {}
+
+impl<T> StaticMut<T> {
+    #[doc(hidden)]
+    /// DO NOT USE DIRECTLY! Created when a `static mut` is labeled with a mutable label.
+    ///
+    /// # Safety
+    ///
+    /// `item` must point to a `static mut`.
+    pub unsafe fn __new(item: *mut T) -> Self {
+        let lock = *LOCKS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(item as usize)
+            .or_insert_with(|| Box::leak(Box::new(RwLock::new(()))));
+
+        Self { item, lock }
+    }
+
+    /// Locks the item for reading, blocking while it is locked for writing.
+    pub fn read(&self) -> ReadGuard<T> {
+        ReadGuard {
+            _guard: self.lock.read().unwrap_or_else(PoisonError::into_inner),
+            item: self.item,
+        }
+    }
+
+    /// Locks the item for writing, blocking while it is locked.
+    pub fn write(&self) -> WriteGuard<T> {
+        WriteGuard {
+            _guard: self.lock.write().unwrap_or_else(PoisonError::into_inner),
+            item: self.item,
+        }
+    }
+}
+
+/// Read access to a labeled `static mut`, returned by [`StaticMut::read`].
+pub struct ReadGuard<T: 'static> {
+    _guard: RwLockReadGuard<'static, ()>,
+    item: *mut T,
+}
+
+impl<T> Deref for ReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the item is locked for reading.
+        unsafe { &*self.item }
+    }
+}
+
+/// Write access to a labeled `static mut`, returned by [`StaticMut::write`].
+pub struct WriteGuard<T: 'static> {
+    _guard: RwLockWriteGuard<'static, ()>,
+    item: *mut T,
+}
+
+impl<T> Deref for WriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // Safety: the item is locked for writing.
+        unsafe { &*self.item }
+    }
+}
+
+impl<T> DerefMut for WriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // Safety: the item is locked for writing.
+        unsafe { &mut *self.item }
+    }
+}
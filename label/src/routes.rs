@@ -0,0 +1,423 @@
+//! A router for HTTP-like requests, where every handler is a function with a label.
+//!
+//! Every handler is given the `method` and `path` of the requests it handles as label arguments.
+//! Paths consist of segments separated by `/`, segments starting with `:` are parameters which
+//! match any single segment. Static segments take precedence over parameters, unless the route
+//! with the static segment doesn't handle the method of the request:
+//!
+//! ```
+//! use label::routes::{route, Request, Response, Router};
+//!
+//! #[route::label(method = "GET", path = "/users/:id")]
+//! fn get_user(request: &Request) -> Response {
+//!     Response::ok(format!("user {}", request.param("id").unwrap()))
+//! }
+//!
+//! fn main() {
+//!     let router = Router::from_label().unwrap();
+//!
+//!     let response = router.dispatch(Request::new("GET", "/users/42"));
+//!     assert_eq!(response.body, "user 42");
+//!     assert_eq!(router.dispatch(Request::new("GET", "/posts")).status, 404);
+//! }
+//! ```
+//!
+//! [`route`] is a ready-made label using [`Request`] and [`Response`], but a [`Router`] can
+//! be built from the entries of any label with `method` and `path` arguments. Two handlers
+//! for the same method and path, or paths which use different names for the same parameter,
+//! are reported as an error when building the router.
+//!
+//! This module is only available with the `routes` feature.
+
+use crate::create_label;
+use crate::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+
+/// A request to dispatch to a handler.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// The parameters in the path of the route which matched this request.
+    /// Filled in by [`Router::dispatch`].
+    pub params: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn new(method: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            path: path.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// The value of the path parameter `name`, `id` for a route `/users/:id`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).map(String::as_str)
+    }
+}
+
+/// The response of a handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: body.into(),
+        }
+    }
+
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(404, "Not Found")
+    }
+
+    pub fn method_not_allowed() -> Self {
+        Self::new(405, "Method Not Allowed")
+    }
+}
+
+create_label!(
+    /// A ready-made label for handlers taking a [`Request`] and returning a [`Response`].
+    ///
+    /// Handlers are given the `method` and `path` of the requests they handle as arguments,
+    /// like `#[route::label(method = "GET", path = "/users/:id")]`. Use [`Router::from_label`]
+    /// to build a router from them.
+    pub fn route(&Request) -> Response;
+);
+
+/// A problem found while building a [`Router`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError {
+    /// The `method` or `path` argument was not given to the label of a handler.
+    MissingMetadata {
+        handler: &'static str,
+        key: &'static str,
+    },
+    /// The path of a handler doesn't start with `/` or has an unnamed parameter.
+    InvalidPath { handler: &'static str, path: String },
+    /// Two handlers handle the same requests.
+    Conflict {
+        method: String,
+        path: String,
+        first: &'static str,
+        second: &'static str,
+    },
+    /// Two handlers have a parameter in the same place in their paths, but with a different name.
+    ParameterName {
+        path: String,
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
+impl fmt::Display for RouteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RouteError::MissingMetadata { handler, key } => {
+                write!(f, "handler `{}` has no `{}` argument", handler, key)
+            }
+            RouteError::InvalidPath { handler, path } => {
+                write!(f, "handler `{}` has an invalid path `{}`", handler, path)
+            }
+            RouteError::Conflict {
+                method,
+                path,
+                first,
+                second,
+            } => write!(
+                f,
+                "handlers `{}` and `{}` both handle {} {}",
+                first, second, method, path
+            ),
+            RouteError::ParameterName {
+                path,
+                first,
+                second,
+            } => write!(
+                f,
+                "handlers `{}` and `{}` use different names for the same parameter in `{}`",
+                first, second, path
+            ),
+        }
+    }
+}
+
+impl Error for RouteError {}
+
+/// A route in a [`Router`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route<H> {
+    pub name: &'static str,
+    pub handler: H,
+}
+
+/// The route found for a request by [`Router::find`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match<H> {
+    pub name: &'static str,
+    pub handler: H,
+    pub params: HashMap<String, String>,
+}
+
+/// Why [`Router::find`] did not find a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotFound {
+    /// No route has this path.
+    Path,
+    /// Some route has this path, but not for this method.
+    Method,
+}
+
+#[derive(Debug)]
+struct Node<H> {
+    statics: BTreeMap<String, Node<H>>,
+    param: Option<(String, Box<Node<H>>)>,
+    // the full path of routes ending here, and the route per method.
+    routes: BTreeMap<String, (String, Route<H>)>,
+}
+
+impl<H> Default for Node<H> {
+    fn default() -> Self {
+        Self {
+            statics: BTreeMap::new(),
+            param: None,
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+impl<H: Copy> Node<H> {
+    // `path_found` is set when some route has the path, even if not for `method`.
+    fn find<'a>(
+        &'a self,
+        segments: &[&str],
+        method: &str,
+        params: &mut Vec<(&'a str, String)>,
+        path_found: &mut bool,
+    ) -> Option<Route<H>> {
+        let (first, rest) = match segments.split_first() {
+            Some(i) => i,
+            None if self.routes.is_empty() => return None,
+            None => {
+                *path_found = true;
+                return self.routes.get(method).map(|(_, route)| *route);
+            }
+        };
+
+        // static segments take precedence over parameters, if they have a route for the method.
+        if let Some(res) = self
+            .statics
+            .get(*first)
+            .and_then(|i| i.find(rest, method, params, path_found))
+        {
+            return Some(res);
+        }
+
+        let (name, node) = self.param.as_ref()?;
+        params.push((name, first.to_string()));
+        match node.find(rest, method, params, path_found) {
+            Some(i) => Some(i),
+            None => {
+                params.pop();
+                None
+            }
+        }
+    }
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|i| !i.is_empty())
+}
+
+/// A path trie, mapping a method and path to a handler.
+#[derive(Debug)]
+pub struct Router<H> {
+    root: Node<H>,
+}
+
+impl<H> Default for Router<H> {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+        }
+    }
+}
+
+impl<H: Copy> Router<H> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a router from the entries of a label, using their `method` and `path` arguments.
+    pub fn from_entries(entries: impl IntoIterator<Item = Entry<H>>) -> Result<Self, RouteError> {
+        let mut res = Self::new();
+        for entry in entries {
+            let metadata = |key| {
+                entry.meta(key).ok_or(RouteError::MissingMetadata {
+                    handler: entry.name(),
+                    key,
+                })
+            };
+            res.add(
+                metadata("method")?,
+                metadata("path")?,
+                entry.name(),
+                entry.item(),
+            )?;
+        }
+
+        Ok(res)
+    }
+
+    /// Adds a handler named `name` for requests with `method` and `path`.
+    pub fn add(
+        &mut self,
+        method: &str,
+        path: &str,
+        name: &'static str,
+        handler: H,
+    ) -> Result<(), RouteError> {
+        let invalid = || RouteError::InvalidPath {
+            handler: name,
+            path: path.to_string(),
+        };
+        if !path.starts_with('/') {
+            return Err(invalid());
+        }
+
+        let mut node = &mut self.root;
+        for segment in segments(path) {
+            node = match segment.strip_prefix(':') {
+                Some("") => return Err(invalid()),
+                Some(param) => {
+                    let (existing, child) = node
+                        .param
+                        .get_or_insert_with(|| (param.to_string(), Box::default()));
+
+                    if existing != param {
+                        // report the conflict with any route using the other name.
+                        return Err(RouteError::ParameterName {
+                            path: path.to_string(),
+                            first: first_route(child).map_or("", |i| i.name),
+                            second: name,
+                        });
+                    }
+                    child.as_mut()
+                }
+                None => node.statics.entry(segment.to_string()).or_default(),
+            };
+        }
+
+        let method = method.to_uppercase();
+        if let Some((_, existing)) = node.routes.get(&method) {
+            return Err(RouteError::Conflict {
+                method,
+                path: path.to_string(),
+                first: existing.name,
+                second: name,
+            });
+        }
+        node.routes
+            .insert(method, (path.to_string(), Route { name, handler }));
+
+        Ok(())
+    }
+
+    /// Finds the route for a request with `method` and `path`, together with the values of its parameters.
+    pub fn find(&self, method: &str, path: &str) -> Result<Match<H>, NotFound> {
+        let segments: Vec<_> = segments(path).collect();
+        let mut params = Vec::new();
+
+        let mut path_found = false;
+
+        let route = self
+            .root
+            .find(
+                &segments,
+                &method.to_uppercase(),
+                &mut params,
+                &mut path_found,
+            )
+            .ok_or(if path_found {
+                NotFound::Method
+            } else {
+                NotFound::Path
+            })?;
+
+        Ok(Match {
+            name: route.name,
+            handler: route.handler,
+            params: params
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        })
+    }
+
+    /// All routes as `(method, path, route)`, ordered by path.
+    pub fn routes(&self) -> Vec<(&str, &str, Route<H>)> {
+        fn collect<'a, H: Copy>(node: &'a Node<H>, res: &mut Vec<(&'a str, &'a str, Route<H>)>) {
+            for (method, (path, route)) in &node.routes {
+                res.push((method, path, *route));
+            }
+            for child in node.statics.values() {
+                collect(child, res);
+            }
+            if let Some((_, child)) = &node.param {
+                collect(child, res);
+            }
+        }
+
+        let mut res = Vec::new();
+        collect(&self.root, &mut res);
+        res.sort_by(|a, b| (a.1, a.0).cmp(&(b.1, b.0)));
+        res
+    }
+}
+
+fn first_route<H: Copy>(node: &Node<H>) -> Option<Route<H>> {
+    node.routes
+        .values()
+        .map(|(_, route)| *route)
+        .next()
+        .or_else(|| node.statics.values().find_map(first_route))
+        .or_else(|| node.param.as_ref().and_then(|(_, i)| first_route(i)))
+}
+
+impl Router<fn(&Request) -> Response> {
+    /// Builds a router from all handlers with the [`route`] label.
+    pub fn from_label() -> Result<Self, RouteError> {
+        Self::from_entries(route::iter_entries())
+    }
+
+    /// Calls the handler for `request`, with the parameters of its path filled in.
+    /// Responds with 404 or 405 when there is no handler.
+    pub fn dispatch(&self, mut request: Request) -> Response {
+        match self.find(&request.method, &request.path) {
+            Ok(found) => {
+                request.params = found.params;
+                (found.handler)(&request)
+            }
+            Err(NotFound::Path) => Response::not_found(),
+            Err(NotFound::Method) => Response::method_not_allowed(),
+        }
+    }
+}
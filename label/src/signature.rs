@@ -0,0 +1,16 @@
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "function `{Function}` has signature `{Self}` but label `{Label}` expects `{Expected}`",
+    label = "expected `{Expected}`"
+)]
+// Checks the signature of labeled functions. `Function` and `Label` are types named after the
+// function and the label, generated where the function is labeled, so the error can name them.
+pub trait __Signature<Function, Label, Expected> {
+    fn __coerce(self) -> Expected;
+}
+
+impl<F, L, T> __Signature<F, L, T> for T {
+    fn __coerce(self) -> T {
+        self
+    }
+}
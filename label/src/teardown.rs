@@ -0,0 +1,172 @@
+//! Teardown hooks, the counterpart of [init](crate::init) hooks.
+//!
+//! Hooks are functions labeled with `teardown::label`. They run in reverse registration order
+//! when the guard returned by [`run_all`] is dropped, or otherwise when the process exits:
+//!
+//! ```
+//! use label::{init, teardown};
+//!
+//! #[init::label]
+//! fn connect() {
+//!     println!("connecting to the database");
+//! }
+//!
+//! #[teardown::label(timeout_ms = 500)]
+//! fn disconnect() {
+//!     println!("disconnecting from the database");
+//! }
+//!
+//! fn main() {
+//!     init::run_all().unwrap();
+//!     let _guard = teardown::run_all();
+//!
+//!     // the rest of main
+//! }
+//! ```
+//!
+//! Every hook runs on its own thread. A hook which panics doesn't stop the other hooks,
+//! and the remaining hooks are not kept waiting by a hook which takes longer than its timeout.
+//! The timeout is given in milliseconds with the `timeout_ms` argument, and is [`DEFAULT_TIMEOUT`]
+//! when not given. A `timeout_ms` which is not a non-negative integer is a compile error. Hooks run
+//! at most once, and hooks which panicked or timed out are reported on stderr.
+//!
+//! Note that hooks which run at process exit only run when the process exits normally,
+//! not when it is killed or aborts.
+
+use crate::catch::catch;
+use crate::create_label;
+use crate::Entry;
+use ctor::dtor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+mod registry {
+    use super::*;
+
+    create_label!(
+        #[label(unsigned = timeout_ms)]
+        pub fn hooks() -> ();
+    );
+}
+
+pub use self::registry::hooks::{add, iter, iter_entries, iter_named, label};
+
+/// The timeout of hooks without a `timeout_ms` argument.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+static RAN: AtomicBool = AtomicBool::new(false);
+
+/// How a hook ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Finished,
+    /// The hook panicked, with the panic message.
+    Panicked(String),
+    /// The hook did not finish within its timeout. It may still be running.
+    TimedOut(Duration),
+}
+
+fn timeout(entry: &Entry<fn()>) -> Duration {
+    entry
+        .meta("timeout_ms")
+        .and_then(|i| i.parse().ok())
+        .map_or(DEFAULT_TIMEOUT, Duration::from_millis)
+}
+
+fn run_hook(entry: Entry<fn()>) -> Status {
+    let timeout = timeout(&entry);
+    let (sender, receiver) = mpsc::channel();
+
+    let spawned = thread::Builder::new()
+        .name(format!("teardown {}", entry.name()))
+        .spawn(move || {
+            let _ = sender.send(catch(entry.item()));
+        });
+
+    let result = match spawned {
+        Ok(_) => receiver.recv_timeout(timeout),
+        // without a thread there can be no timeout, but the hook should still run.
+        Err(_) => Ok(catch(entry.item())),
+    };
+
+    match result {
+        Ok(Ok(())) => Status::Finished,
+        Ok(Err(panic)) => Status::Panicked(panic.message),
+        Err(RecvTimeoutError::Timeout) => Status::TimedOut(timeout),
+        Err(RecvTimeoutError::Disconnected) => Status::Panicked("hook thread died".to_string()),
+    }
+}
+
+/// Runs `entries` in reverse order, each with the panic isolation and timeout of teardown hooks.
+/// This can be used for any label of `fn()`. An invalid `timeout_ms` is [`DEFAULT_TIMEOUT`], which
+/// labels can rule out with `#[label(unsigned = timeout_ms)]`.
+pub fn run(entries: impl IntoIterator<Item = Entry<fn()>>) -> Vec<(&'static str, Status)> {
+    let entries: Vec<_> = entries.into_iter().collect();
+
+    entries
+        .into_iter()
+        .rev()
+        .map(|entry| (entry.name(), run_hook(entry)))
+        .collect()
+}
+
+fn report(statuses: &[(&'static str, Status)]) {
+    for (name, status) in statuses {
+        match status {
+            Status::Finished => {}
+            Status::Panicked(message) => {
+                eprintln!("teardown hook `{}` panicked: {}", name, message)
+            }
+            Status::TimedOut(timeout) => eprintln!(
+                "teardown hook `{}` did not finish within {:?}",
+                name, timeout
+            ),
+        }
+    }
+}
+
+/// Runs the hooks with the `teardown` label, unless they already ran.
+fn run_once() -> Option<Vec<(&'static str, Status)>> {
+    if RAN.swap(true, Ordering::SeqCst) {
+        None
+    } else {
+        Some(run(iter_entries()))
+    }
+}
+
+/// Runs the teardown hooks when dropped, instead of at process exit.
+#[must_use = "the teardown hooks run when the guard is dropped"]
+pub struct Guard {
+    _private: (),
+}
+
+impl Guard {
+    /// Runs the teardown hooks now, and returns how each of them ended.
+    /// Returns an empty list if the hooks already ran.
+    pub fn finish(self) -> Vec<(&'static str, Status)> {
+        std::mem::forget(self);
+        run_once().unwrap_or_default()
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(statuses) = run_once() {
+            report(&statuses);
+        }
+    }
+}
+
+/// Returns a guard which runs the hooks with the `teardown` label when dropped.
+pub fn run_all() -> Guard {
+    Guard { _private: () }
+}
+
+#[dtor]
+fn run_at_exit() {
+    if let Some(statuses) = run_once() {
+        report(&statuses);
+    }
+}
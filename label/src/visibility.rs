@@ -0,0 +1,18 @@
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "`{Item}` can't be labeled with `{Label}`, because of its visibility",
+    label = "this visibility is not allowed by `{Label}`",
+    note = "labels allow visibilities with `#[label(require = ..)]` and `#[label(forbid = ..)]`"
+)]
+// Checks the visibility of labeled items. Every label implements this for the visibilities it
+// allows, and `Item` and `Label` are types named after the item and the label, like in `__Signature`.
+pub trait __Visible<Item, Label, Visibility> {}
+
+/// The kinds of `label::Visibility`, as types.
+pub mod kinds {
+    pub struct Public;
+    pub struct Crate;
+    pub struct Super;
+    pub struct Restricted;
+    pub struct Private;
+}
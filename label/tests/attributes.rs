@@ -0,0 +1,73 @@
+use label::create_label;
+
+create_label!(
+    #[label(attribute = register)]
+    fn handlers() -> u32;
+    #[label(attribute = setting)]
+    static settings: u32;
+);
+
+use handlers::register as handler;
+
+#[handlers::register]
+fn with_name() -> u32 {
+    1
+}
+
+#[handlers::label]
+fn with_label() -> u32 {
+    2
+}
+
+#[handler(key = "value")]
+fn with_alias() -> u32 {
+    3
+}
+
+#[handlers::register]
+#[handlers::label]
+fn with_both() -> u32 {
+    4
+}
+
+#[cfg_attr(test, handler)]
+fn behind_cfg_attr() -> u32 {
+    5
+}
+
+mod inner {
+    #[super::handler]
+    pub fn in_module() -> u32 {
+        6
+    }
+}
+
+#[settings::setting]
+static TIMEOUT: u32 = 30;
+
+#[test]
+fn test_attribute_names() {
+    let mut registered: Vec<_> = handlers::iter_named()
+        .map(|(name, f)| (name, f()))
+        .collect();
+    registered.sort();
+    assert_eq!(
+        registered,
+        vec![
+            ("behind_cfg_attr", 5),
+            ("in_module", 6),
+            ("with_alias", 3),
+            ("with_both", 4),
+            ("with_both", 4),
+            ("with_label", 2),
+            ("with_name", 1),
+        ]
+    );
+
+    let entry = handlers::iter_entries()
+        .find(|i| i.name() == "with_alias")
+        .unwrap();
+    assert_eq!(entry.meta("key"), Some("value"));
+
+    assert_eq!(settings::iter().collect::<Vec<_>>(), vec![&30]);
+}
@@ -0,0 +1,169 @@
+use label::bench::{parse_baseline, run, Arguments, Bencher, Format};
+use label::create_label;
+use std::fs;
+
+create_label!(
+    fn bench(&mut Bencher);
+);
+
+#[bench::label]
+fn sum(b: &mut Bencher) {
+    b.iter(|| (0..100u64).sum::<u64>());
+}
+
+#[bench::label]
+fn sum_squares(b: &mut Bencher) {
+    b.iter(|| (0..100u64).map(|i| i * i).sum::<u64>());
+}
+
+#[bench::label]
+fn panics(_b: &mut Bencher) {
+    panic!("broken benchmark");
+}
+
+fn args(args: &[&str]) -> Arguments {
+    let mut res =
+        Arguments::parse(Some("--bench").into_iter().chain(args.iter().copied())).unwrap();
+    res.warmup = std::time::Duration::from_millis(1);
+    res.measurement_time = std::time::Duration::from_millis(5);
+    res.samples = 5;
+    res
+}
+
+#[test]
+fn test_measure() {
+    let mut out = Vec::new();
+    let conclusion = run(&args(&["sum"]), bench::iter_named(), &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+
+    assert_eq!(conclusion.filtered_out, 1);
+    assert_eq!(conclusion.measurements.len(), 2);
+    for i in &conclusion.measurements {
+        assert_eq!(i.samples, 5);
+        assert!(i.iterations >= 1);
+        assert!(i.mean > 0.0);
+        assert!(i.stddev >= 0.0);
+        assert!(i.baseline.is_none());
+    }
+    assert!(out.contains("sum "));
+    assert!(out.contains("time: mean"));
+}
+
+#[test]
+fn test_panic() {
+    let mut out = Vec::new();
+    let conclusion = run(&args(&["panics"]), bench::iter_named(), &mut out).unwrap();
+
+    assert!(conclusion.has_failed());
+    assert_eq!(
+        conclusion.failed,
+        vec![("panics".to_string(), "broken benchmark".to_string())]
+    );
+}
+
+#[test]
+fn test_list_and_test_mode() {
+    assert!(!Arguments::parse(Vec::<String>::new()).unwrap().bench);
+
+    let mut out = Vec::new();
+    run(
+        &args(&["--list", "sum", "--exact"]),
+        bench::iter_named(),
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "sum: bench\n");
+
+    let mut out = Vec::new();
+    let conclusion = run(&args(&["--test", "sum"]), bench::iter_named(), &mut out).unwrap();
+    assert!(conclusion.measurements.is_empty());
+    assert!(String::from_utf8(out).unwrap().contains("sum_squares"));
+}
+
+#[test]
+fn test_formats() {
+    let mut out = Vec::new();
+    run(
+        &args(&["--format", "csv", "sum"]),
+        bench::iter_named(),
+        &mut out,
+    )
+    .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(
+        lines[0],
+        "name,mean_ns,median_ns,stddev_ns,samples,iterations"
+    );
+    assert!(lines[1].starts_with("sum,"));
+    assert!(lines[2].starts_with("sum_squares,"));
+
+    let mut out = Vec::new();
+    run(
+        &args(&["--format=json", "sum_squares"]),
+        bench::iter_named(),
+        &mut out,
+    )
+    .unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("[\n  { \"name\": \"sum_squares\", \"mean_ns\": "));
+    assert!(out.ends_with("\"baseline_mean_ns\": null, \"change\": null }\n]\n"));
+
+    let mut out = Vec::new();
+    let test_mode = Arguments::parse(vec!["--format", "json", "sum"]).unwrap();
+    run(&test_mode, bench::iter_named(), &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "[]\n");
+
+    let mut out = Vec::new();
+    let test_mode = Arguments::parse(vec!["--format", "csv", "sum"]).unwrap();
+    run(&test_mode, bench::iter_named(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "name,mean_ns,median_ns,stddev_ns,samples,iterations\n"
+    );
+}
+
+#[test]
+fn test_baseline() {
+    let path = std::env::temp_dir().join(format!("label-baseline-{}.csv", std::process::id()));
+    let path_str = path.to_str().unwrap();
+
+    let saved = run(
+        &args(&["sum", "--save-baseline", path_str]),
+        bench::iter_named(),
+        Vec::new(),
+    )
+    .unwrap();
+    let baseline = parse_baseline(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(baseline.len(), 2);
+    assert_eq!(baseline["sum"], saved.measurements[0].mean);
+
+    let mut out = Vec::new();
+    let compared = run(
+        &args(&["sum", "--baseline", path_str]),
+        bench::iter_named(),
+        &mut out,
+    )
+    .unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        compared.measurements[0].baseline,
+        Some(saved.measurements[0].mean)
+    );
+    assert!(compared.measurements[0].change().is_some());
+    assert!(String::from_utf8(out).unwrap().contains("change: "));
+}
+
+#[test]
+fn test_arguments() {
+    let args = Arguments::parse(vec!["--samples", "3", "--warmup=10", "--bench"]).unwrap();
+    assert_eq!(args.samples, 3);
+    assert!(args.bench);
+    assert_eq!(args.warmup, std::time::Duration::from_millis(10));
+    assert_eq!(args.format, Format::Pretty);
+
+    assert!(Arguments::parse(vec!["--samples", "0"]).is_err());
+    assert!(Arguments::parse(vec!["--format", "xml"]).is_err());
+    assert!(parse_baseline("name,median_ns\nsum,1.0\n").is_err());
+}
@@ -0,0 +1,219 @@
+use label::create_label;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+pub struct NotClone;
+
+pub struct Result<T> {
+    pub value: T,
+}
+
+create_label!(
+    fn greet(name: String) -> String;
+    fn check(u8) -> std::result::Result<u8, String>;
+    fn lookup(&str) -> Option<usize>;
+    fn count(&mut usize);
+    fn split(Option<&str>) -> Vec<&str>;
+    fn first<'a>(&'a [u8]) -> &'a u8;
+    fn consume(NotClone);
+    fn borrow_mutably(&mut [u8]) -> &mut u8;
+    fn not_std(u8) -> Result<u8>;
+    fn shared(Rc<String>) -> usize;
+    fn fragile(u8) -> u8;
+);
+
+#[greet::label]
+fn hello(name: String) -> String {
+    format!("Hello, {}!", name)
+}
+
+#[greet::label]
+fn goodbye(name: String) -> String {
+    format!("Goodbye, {}!", name)
+}
+
+#[check::label]
+fn small(x: u8) -> std::result::Result<u8, String> {
+    if x < 10 {
+        Ok(x)
+    } else {
+        Err(format!("{} is not small", x))
+    }
+}
+
+#[check::label]
+fn even(x: u8) -> std::result::Result<u8, String> {
+    if x % 2 == 1 {
+        Err(format!("{} is not even", x))
+    } else {
+        Ok(x)
+    }
+}
+
+#[lookup::label]
+fn length(key: &str) -> Option<usize> {
+    if key.is_empty() {
+        None
+    } else {
+        Some(key.len())
+    }
+}
+
+#[lookup::label]
+fn zero(_: &str) -> Option<usize> {
+    Some(0)
+}
+
+#[count::label]
+fn increment(counter: &mut usize) {
+    *counter += 1;
+}
+
+#[count::label]
+fn increment_again(counter: &mut usize) {
+    *counter += 1;
+}
+
+#[split::label]
+fn words(text: Option<&str>) -> Vec<&str> {
+    text.map(|i| i.split(' ').collect()).unwrap_or_default()
+}
+
+#[first::label]
+fn head(bytes: &[u8]) -> &u8 {
+    &bytes[0]
+}
+
+#[consume::label]
+fn drop_it(_: NotClone) {}
+
+#[borrow_mutably::label]
+fn last(bytes: &mut [u8]) -> &mut u8 {
+    bytes.last_mut().unwrap()
+}
+
+#[shared::label]
+fn references(s: Rc<String>) -> usize {
+    Rc::strong_count(&s)
+}
+
+#[fragile::label]
+fn panics(x: u8) -> u8 {
+    if x > 0 {
+        panic!("{} is too large", x);
+    }
+    x
+}
+
+#[fragile::label]
+fn survives(x: u8) -> u8 {
+    x + 1
+}
+
+#[test]
+fn test_call_all() {
+    let greetings: HashSet<_> = greet::call_all("label".to_string()).into_iter().collect();
+    assert_eq!(greetings.len(), 2);
+    assert!(greetings.contains(&("hello", "Hello, label!".to_string())));
+    assert!(greetings.contains(&("goodbye", "Goodbye, label!".to_string())));
+
+    assert_eq!(
+        split::call_all(Some("a b")),
+        vec![("words", vec!["a", "b"])]
+    );
+
+    let bytes = vec![1, 2, 3];
+    assert_eq!(first::call_all(&bytes), vec![("head", &1)]);
+}
+
+#[test]
+fn test_call_all_mut() {
+    let mut counter = 0;
+    assert_eq!(count::call_all(&mut counter).len(), 2);
+    assert_eq!(counter, 2);
+}
+
+#[test]
+fn test_clone_per_call() {
+    let s = Rc::new("shared".to_string());
+    // every function gets its own clone, while the original is kept.
+    assert_eq!(shared::call_all(s.clone()), vec![("references", 3)]);
+    assert_eq!(Rc::strong_count(&s), 1);
+}
+
+#[test]
+fn test_try_call_all() {
+    assert_eq!(check::try_call_all(4).unwrap().len(), 2);
+
+    let (name, error) = check::try_call_all(11).unwrap_err();
+    assert!(name == "small" || name == "even");
+    assert!(error.ends_with("is not small") || error.ends_with("is not even"));
+}
+
+#[test]
+fn test_call_all_collect_errors() {
+    assert_eq!(check::call_all_collect_errors(2).unwrap().len(), 2);
+
+    let mut errors = check::call_all_collect_errors(11).unwrap_err();
+    errors.sort();
+    assert_eq!(
+        errors,
+        vec![
+            ("even", "11 is not even".to_string()),
+            ("small", "11 is not small".to_string())
+        ]
+    );
+
+    assert_eq!(
+        check::call_all_collect_errors(12).unwrap_err(),
+        vec![("small", "12 is not small".to_string())]
+    );
+}
+
+#[test]
+fn test_call_first_some() {
+    let (name, value) = lookup::call_first_some("label").unwrap();
+    assert_eq!(value, if name == "length" { 5 } else { 0 });
+
+    assert_eq!(lookup::call_first_some(""), Some(("zero", 0)));
+}
+
+#[test]
+fn test_uncallable() {
+    // labels taking arguments which can't be cloned, returning a borrow of a `&mut` argument or returning
+    // something which is only called `Result` still work, without (callable) `call_all` functions.
+    consume::iter().for_each(|i| i(NotClone));
+
+    let mut bytes = [1, 2];
+    for i in borrow_mutably::iter() {
+        *i(&mut bytes) += 1;
+    }
+    assert_eq!(bytes, [1, 3]);
+
+    assert_eq!(not_std::iter().count(), 0);
+}
+
+#[test]
+fn test_call_all_isolated() {
+    let mut outcomes = fragile::call_all_isolated(1);
+    outcomes.sort_by_key(|i| i.name);
+
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes[0].name, "panics");
+    assert!(outcomes[0].panicked());
+    assert_eq!(outcomes[0].location.file(), "label/tests/calls.rs");
+
+    let panic = outcomes[0].result.as_ref().unwrap_err();
+    assert_eq!(panic.message, "1 is too large");
+    let location = panic.location.as_ref().unwrap();
+    assert!(location.starts_with("label/tests/calls.rs:"));
+    assert_eq!(
+        panic.to_string(),
+        format!("panicked at {}: 1 is too large", location)
+    );
+
+    assert_eq!(outcomes[1].name, "survives");
+    assert_eq!(outcomes[1].result, Ok(2));
+
+    assert!(fragile::call_all_isolated(0).iter().all(|i| !i.panicked()));
+}
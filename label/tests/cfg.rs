@@ -0,0 +1,68 @@
+use label::create_label;
+
+create_label!(
+    fn configured() -> u32;
+    fn other() -> u32;
+);
+
+#[cfg(test)]
+#[configured::label]
+fn only_in_tests() -> u32 {
+    1
+}
+
+#[cfg(not(test))]
+#[configured::label]
+fn never_in_tests() -> u32 {
+    2
+}
+
+#[configured::label]
+#[cfg(any())]
+fn removed_after_label() -> u32 {
+    3
+}
+
+#[cfg_attr(feature = "rayon", configured::label)]
+#[allow(dead_code)]
+fn with_rayon() -> u32 {
+    4
+}
+
+#[cfg_attr(not(feature = "rayon"), configured::label(key = "value"))]
+#[allow(dead_code)]
+fn without_rayon() -> u32 {
+    5
+}
+
+#[configured::label]
+#[cfg_attr(feature = "routes", other::label)]
+fn with_routes() -> u32 {
+    6
+}
+
+#[test]
+fn test_cfg() {
+    let mut names: Vec<_> = configured::iter_named().map(|(n, _)| n).collect();
+    names.sort_unstable();
+
+    let mut expected = vec!["only_in_tests", "with_routes"];
+    if cfg!(feature = "rayon") {
+        expected.push("with_rayon");
+    } else {
+        expected.push("without_rayon");
+    }
+    expected.sort_unstable();
+    assert_eq!(names, expected);
+
+    let other: Vec<_> = other::iter_named().map(|(n, _)| n).collect();
+    if cfg!(feature = "routes") {
+        assert_eq!(other, ["with_routes"]);
+    } else {
+        assert!(other.is_empty());
+    }
+
+    if let Some(entry) = configured::iter_entries().find(|i| i.name() == "without_rayon") {
+        assert_eq!(entry.meta("key"), Some("value"));
+    }
+}
@@ -0,0 +1,138 @@
+use label::cli::{Command, Dispatcher, USAGE_ERROR};
+use label::create_label;
+
+create_label!(
+    fn command(&[String]) -> i32;
+);
+
+/// Deploys the arguments.
+///
+/// Returns the number of deployed arguments.
+#[command::label]
+fn deploy(args: &[String]) -> i32 {
+    args.len() as i32
+}
+
+#[command::label]
+fn status(_args: &[String]) -> i32 {
+    0
+}
+
+#[command::label]
+fn destroy(_args: &[String]) -> i32 {
+    1
+}
+
+fn dispatch(args: &[&str]) -> (i32, String, String) {
+    let dispatcher = Dispatcher::new(command::iter_named());
+    let (mut out, mut err) = (Vec::new(), Vec::new());
+    let code = dispatcher.dispatch(args.iter().copied(), &mut out, &mut err);
+
+    (
+        code,
+        String::from_utf8(out).unwrap(),
+        String::from_utf8(err).unwrap(),
+    )
+}
+
+#[test]
+fn test_dispatch() {
+    assert_eq!(dispatch(&["tool", "deploy", "a", "b"]).0, 2);
+    assert_eq!(dispatch(&["tool", "status"]).0, 0);
+    assert_eq!(dispatch(&["tool", "destroy", "--force"]).0, 1);
+}
+
+#[test]
+fn test_help() {
+    let (code, out, err) = dispatch(&["/usr/bin/tool", "--help"]);
+
+    assert_eq!(code, 0);
+    assert_eq!(err, "");
+    assert_eq!(
+        out,
+        "Usage: tool <command> [arguments...]\n\
+         \n\
+         Commands:\n    \
+             deploy\n    \
+             destroy\n    \
+             status\n    \
+             help     Print this message\n"
+    );
+
+    let (code, _, err) = dispatch(&["tool"]);
+    assert_eq!(code, USAGE_ERROR);
+    assert!(err.starts_with("Usage: tool <command>"));
+}
+
+#[test]
+fn test_doc_descriptions() {
+    let dispatcher = Dispatcher::new(command::iter_entries());
+
+    assert!(dispatcher
+        .help("tool")
+        .contains("    deploy   Deploys the arguments.\n    destroy\n"));
+}
+
+#[test]
+fn test_descriptions() {
+    let dispatcher =
+        Dispatcher::new(command::iter_named().map(|(name, func)| {
+            Command::new(name, func).with_description(format!("Runs {}", name))
+        }));
+
+    assert!(dispatcher
+        .help("tool")
+        .contains("    deploy   Runs deploy\n    destroy  Runs destroy\n"));
+}
+
+#[test]
+fn test_unknown_command() {
+    let (code, out, err) = dispatch(&["tool", "deplyo"]);
+
+    assert_eq!(code, USAGE_ERROR);
+    assert_eq!(out, "");
+    assert_eq!(
+        err,
+        "error: unknown command `deplyo`\n\
+         \n  tip: a similar command exists: `deploy`\n\
+         \nRun `tool --help` for a list of commands.\n"
+    );
+
+    let (code, _, err) = dispatch(&["tool", "something"]);
+    assert_eq!(code, USAGE_ERROR);
+    assert!(!err.contains("tip"));
+}
+
+#[test]
+fn test_suggest() {
+    let dispatcher = Dispatcher::new(command::iter_named());
+
+    assert_eq!(dispatcher.suggest("destory"), vec!["destroy"]);
+    assert_eq!(dispatcher.suggest("list"), Vec::<&str>::new());
+    assert_eq!(dispatcher.suggest("statu"), vec!["status"]);
+}
+
+#[test]
+#[should_panic(expected = "there is more than one command named `deploy`")]
+fn test_duplicate_names() {
+    Dispatcher::new(vec![
+        Command::new("deploy", deploy),
+        Command::new("deploy", status),
+    ]);
+}
+
+#[test]
+fn test_custom_help() {
+    let dispatcher = Dispatcher::new(vec![
+        Command::new("deploy", deploy),
+        Command::new("help", status).with_description("Custom help."),
+    ]);
+
+    let help = dispatcher.help("tool");
+    assert!(help.ends_with("    deploy\n    help    Custom help.\n"));
+    assert!(!help.contains("Print this message"));
+    assert_eq!(
+        dispatcher.dispatch(vec!["tool", "help"], Vec::new(), Vec::new()),
+        0
+    );
+}
@@ -0,0 +1,188 @@
+use label::config::{self, load_entries, ConfigError, ConfigValue, Key};
+use label::create_label;
+
+/// The port to listen on.
+#[config::label(env = "PORT", default = 8080)]
+static PORT: ConfigValue<u16>;
+
+/// The name of the server,
+/// shown on every page.
+#[config::label(key = "server.name")]
+static NAME: ConfigValue<String>;
+
+#[label::config::label(key = "server.debug", default = false)]
+static DEBUG: ConfigValue<bool>;
+
+#[config::label(env = "RATIO")]
+static RATIO: ConfigValue<f64>;
+
+#[test]
+fn test_load_from() {
+    assert_eq!(PORT.try_get(), None);
+
+    let file = r#"
+        # comments are ignored
+        ratio = 0.5
+
+        [server]
+        name = "label \"server\" # 1" # the name
+        debug = true
+    "#;
+    let env = vec![
+        ("PORT".to_string(), "3000".to_string()),
+        ("RATIO".to_string(), "0.25".to_string()),
+    ];
+    config::load_from(env, file).unwrap();
+
+    assert_eq!(PORT.get(), 3000);
+    assert_eq!(NAME.get(), "label \"server\" # 1");
+    assert!(DEBUG.get());
+    // the environment takes precedence over the file.
+    assert_eq!(RATIO.get(), 0.25);
+
+    assert_eq!(
+        config::describe(),
+        "\
+port: u16 (env PORT) (default 8080)
+    The port to listen on.
+ratio: f64 (env RATIO)
+server.debug: bool (default false)
+server.name: alloc::string::String
+    The name of the server,
+    shown on every page.
+"
+    );
+}
+
+create_label!(
+    static other: dyn Key;
+);
+
+#[other::label(default = 1)]
+static WITH_DEFAULT: ConfigValue<u8>;
+
+#[other::label]
+static REQUIRED: ConfigValue<u8>;
+
+#[other::label]
+static INVALID: ConfigValue<u8>;
+
+#[test]
+fn test_errors() {
+    let file = "
+        invalid = 300
+        unknown = 'value'
+        [table
+    ";
+    let mut errors = load_entries(other::iter_entries(), Vec::new(), file).unwrap_err();
+    errors.sort_by_key(|i| i.to_string());
+
+    assert_eq!(
+        errors,
+        vec![
+            ConfigError::Syntax {
+                line: 4,
+                message: "expected `]`".to_string()
+            },
+            // the file is not used when it is invalid.
+            ConfigError::Missing {
+                key: "invalid".to_string()
+            },
+            ConfigError::Missing {
+                key: "required".to_string()
+            },
+        ]
+    );
+
+    let file = "
+        invalid = 300
+        unknown = 'value'
+        required = 2
+    ";
+    let mut errors = load_entries(other::iter_entries(), Vec::new(), file).unwrap_err();
+    errors.sort_by_key(|i| i.to_string());
+
+    assert_eq!(
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        vec![
+            "invalid value `300` for key `invalid`: number too large to fit in target type",
+            "line 3: unknown key `unknown`",
+        ]
+    );
+    // valid keys are still loaded.
+    assert_eq!(WITH_DEFAULT.get(), 1);
+    assert_eq!(REQUIRED.get(), 2);
+    assert_eq!(INVALID.try_get(), None);
+}
+
+create_label!(
+    static duplicated: dyn Key;
+);
+
+mod first {
+    use super::*;
+
+    #[duplicated::label(default = 1)]
+    pub static PORT: ConfigValue<u16>;
+}
+
+mod second {
+    use super::*;
+
+    #[duplicated::label(default = 2)]
+    pub static PORT: ConfigValue<u16>;
+}
+
+#[duplicated::label(key = "first.port", default = 3)]
+static RENAMED: ConfigValue<u16>;
+
+#[test]
+fn test_duplicate_keys() {
+    let errors = load_entries(duplicated::iter_entries(), Vec::new(), "").unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![ConfigError::Duplicate {
+            key: "port".to_string()
+        }]
+    );
+    assert_eq!(errors[0].to_string(), "multiple statics use key `port`");
+    assert_eq!(first::PORT.try_get(), None);
+    assert_eq!(second::PORT.try_get(), None);
+    assert_eq!(RENAMED.get(), 3);
+}
+
+create_label!(
+    static values: dyn Key;
+);
+
+#[values::label]
+static COUNT: ConfigValue<u32>;
+
+#[values::label]
+static WORD: ConfigValue<String>;
+
+#[test]
+fn test_values() {
+    let file = "
+        count = 1_000
+        word = 'foo_bar'
+    ";
+    load_entries(values::iter_entries(), Vec::new(), file).unwrap();
+    assert_eq!(COUNT.get(), 1000);
+    assert_eq!(WORD.get(), "foo_bar");
+
+    // bare words which aren't numbers or booleans are rejected, instead of losing their `_`.
+    let file = "
+        count = 1
+        word = foo_bar
+    ";
+    let errors = load_entries(values::iter_entries(), Vec::new(), file).unwrap_err();
+    assert_eq!(
+        errors[0],
+        ConfigError::Syntax {
+            line: 3,
+            message: "expected a string, number or boolean, not `foo_bar`".to_string()
+        }
+    );
+}
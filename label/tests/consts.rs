@@ -30,3 +30,54 @@ fn test_named() {
         assert_eq!(name, "A")
     }
 }
+
+create_label!(
+    static mut othermutname: usize;
+    static mut readonlyname: usize;
+);
+
+#[staticmutname::label]
+#[othermutname::label]
+static mut C: usize = 0;
+
+#[staticmutname::label]
+static mut D: usize = 10;
+
+// labeling a `static mut` with an immutable label is a compile error, see `tests/ui/static_mut_label.rs`.
+#[readonlyname::label]
+static mut E: usize = 3;
+
+#[test]
+fn test_static_mut_read() {
+    let (name, value) = readonlyname::iter_named().next().unwrap();
+    assert_eq!(name, "E");
+    assert_eq!(*value.read(), 3);
+}
+
+#[test]
+fn test_mutable() {
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..100 {
+                    for mut i in staticmutname::iter_mut() {
+                        *i += 1;
+                    }
+                    // C has both labels, and shares its lock between them.
+                    for i in othermutname::iter() {
+                        *i.write() += 1;
+                    }
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+
+    let mut values: Vec<_> = staticmutname::iter_named()
+        .map(|(name, i)| (name, *i.read()))
+        .collect();
+    values.sort();
+    assert_eq!(values, vec![("C", 800), ("D", 410)]);
+}
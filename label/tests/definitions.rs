@@ -0,0 +1,51 @@
+use label::create_label;
+
+create_label!(
+    /// Handlers which are called for every request.
+    ///
+    /// Doc comments end up on the module of the label.
+    #[label(attribute = handler)]
+    fn handlers() -> u32;
+
+    #[cfg(test)]
+    fn only_in_tests() -> u32;
+
+    #[cfg(not(test))]
+    fn never_in_tests(u8) -> u8;
+
+    #[deprecated(note = "use `handlers` instead")]
+    fn old_handlers() -> u32;
+);
+
+#[cfg(not(test))]
+create_label!(fn only_in_tests(u8) -> u8);
+
+#[handlers::handler]
+fn handle() -> u32 {
+    1
+}
+
+#[only_in_tests::label]
+fn in_tests() -> u32 {
+    2
+}
+
+#[allow(deprecated)]
+#[old_handlers::label]
+fn old_handle() -> u32 {
+    3
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_forwarded_attributes() {
+    assert_eq!(handlers::iter().map(|i| i()).collect::<Vec<_>>(), vec![1]);
+    assert_eq!(
+        only_in_tests::iter().map(|i| i()).collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert_eq!(
+        old_handlers::iter().map(|i| i()).collect::<Vec<_>>(),
+        vec![3]
+    );
+}
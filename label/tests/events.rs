@@ -0,0 +1,116 @@
+use label::create_label;
+use label::events::Propagation;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+pub struct Ping(pub usize);
+
+create_label!(
+    event pinged(Ping);
+    event stoppable(Ping) -> Propagation;
+    event fallible(Ping) -> Result<(), String>;
+);
+
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+
+#[pinged::label]
+fn add(event: &Ping) {
+    TOTAL.fetch_add(event.0, Ordering::SeqCst);
+}
+
+#[pinged::label]
+fn add_again(event: &Ping) {
+    TOTAL.fetch_add(event.0, Ordering::SeqCst);
+}
+
+#[test]
+fn test_emit() {
+    let emitted = pinged::emit(&Ping(3));
+
+    assert_eq!(TOTAL.load(Ordering::SeqCst), 6);
+    assert_eq!(emitted.called.len(), 2);
+    assert_eq!(emitted.stopped_by, None);
+    assert!(emitted.into_result().is_ok());
+}
+
+static ORDER: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+#[stoppable::label(priority = -5)]
+fn low(_event: &Ping) -> Propagation {
+    ORDER.lock().unwrap().push("low");
+    Propagation::Continue
+}
+
+#[stoppable::label(priority = 10)]
+fn high(_event: &Ping) -> Propagation {
+    ORDER.lock().unwrap().push("high");
+    Propagation::Continue
+}
+
+#[stoppable::label]
+fn stopper(event: &Ping) -> Propagation {
+    ORDER.lock().unwrap().push("stopper");
+    if event.0 > 5 {
+        Propagation::Stop
+    } else {
+        Propagation::Continue
+    }
+}
+
+#[test]
+fn test_priority_and_stop() {
+    let emitted = stoppable::emit(&Ping(1));
+    assert_eq!(emitted.called, vec!["high", "stopper", "low"]);
+
+    let emitted = stoppable::emit(&Ping(10));
+    assert_eq!(emitted.called, vec!["high", "stopper"]);
+    assert_eq!(emitted.stopped_by, Some("stopper"));
+
+    assert_eq!(
+        *ORDER.lock().unwrap(),
+        vec!["high", "stopper", "low", "high", "stopper"]
+    );
+}
+
+#[fallible::label(priority = 2)]
+fn fails(event: &Ping) -> Result<(), String> {
+    Err(format!("fails with {}", event.0))
+}
+
+#[fallible::label(priority = 1)]
+fn fails_too(_event: &Ping) -> Result<(), String> {
+    Err("fails too".to_string())
+}
+
+#[fallible::label]
+fn succeeds(_event: &Ping) -> Result<(), String> {
+    Ok(())
+}
+
+#[test]
+fn test_errors() {
+    let emitted = fallible::emit(&Ping(1));
+    assert_eq!(emitted.called, vec!["fails"]);
+    assert_eq!(emitted.errors, vec![("fails", "fails with 1".to_string())]);
+
+    let emitted = fallible::emit_collect(&Ping(2));
+    assert_eq!(emitted.called, vec!["fails", "fails_too", "succeeds"]);
+    assert_eq!(
+        emitted.into_result().unwrap_err(),
+        vec![
+            ("fails", "fails with 2".to_string()),
+            ("fails_too", "fails too".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_subscribers_are_a_label() {
+    let mut names: Vec<_> = pinged::iter_named().map(|(name, _)| name).collect();
+    names.sort_unstable();
+    assert_eq!(names, vec!["add", "add_again"]);
+
+    for subscriber in fallible::iter() {
+        let _ = subscriber(&Ping(0));
+    }
+}
@@ -0,0 +1,64 @@
+use label::flags::{self, Flag, FlagError};
+
+/// Shows the new user interface.
+#[flags::label]
+static NEW_UI: Flag = Flag::off();
+
+#[flags::label]
+static FAST_PATH: Flag = Flag::on();
+
+#[label::flags::label]
+static BETA: Flag = Flag::off();
+
+#[test]
+fn test_flags() {
+    assert_eq!(
+        flags::list(),
+        vec![("BETA", false), ("FAST_PATH", true), ("NEW_UI", false)]
+    );
+
+    flags::set("NEW_UI", true).unwrap();
+    assert!(NEW_UI.is_enabled());
+    assert_eq!(
+        flags::set("OLD_UI", true),
+        Err(FlagError::Unknown {
+            name: "OLD_UI".to_string(),
+            line: None
+        })
+    );
+
+    let file = "
+        # turn off the fast path
+        FAST_PATH = off
+        BETA=true # for now
+        MISSING = on
+        NEW_UI = maybe
+        NEW_UI
+    ";
+    let errors: Vec<_> = flags::load_from_str(file)
+        .unwrap_err()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "line 5: unknown flag `MISSING`",
+            "line 6: expected `true`, `false`, `on` or `off` as value of `NEW_UI`, not `maybe`",
+            "line 7: expected `NAME = value`",
+        ]
+    );
+
+    // valid lines are still applied.
+    assert_eq!(
+        flags::list(),
+        vec![("BETA", true), ("FAST_PATH", false), ("NEW_UI", true)]
+    );
+    assert_eq!(
+        flags::iter_entries()
+            .find(|i| i.name() == "NEW_UI")
+            .unwrap()
+            .doc(),
+        "Shows the new user interface."
+    );
+}
@@ -0,0 +1,148 @@
+use label::create_label;
+use label::harness::{run, Arguments, Conclusion, Format, Test};
+
+create_label!(
+    fn check() -> Result<(), String>;
+);
+
+#[check::label]
+fn passes() -> Result<(), String> {
+    Ok(())
+}
+
+#[check::label]
+fn returns_error() -> Result<(), String> {
+    Err("something went wrong".to_string())
+}
+
+#[check::label]
+fn panics() -> Result<(), String> {
+    panic!("oh no");
+}
+
+#[check::label]
+fn passes_too() -> Result<(), String> {
+    Ok(())
+}
+
+fn run_with(args: &[&str]) -> (Conclusion, String) {
+    let args = Arguments::parse(args.iter().copied()).unwrap();
+    let mut out = Vec::new();
+    let conclusion = run(&args, check::iter_named(), &mut out).unwrap();
+
+    (conclusion, String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_run_all() {
+    let (conclusion, out) = run_with(&["--test-threads", "2"]);
+
+    assert_eq!(
+        conclusion,
+        Conclusion {
+            passed: 2,
+            failed: 2,
+            ignored: 0,
+            filtered_out: 0
+        }
+    );
+    assert!(out.contains("running 4 tests"));
+    assert!(out.contains("test passes ... ok"));
+    assert!(out.contains("test returns_error ... FAILED"));
+    assert!(out.contains("---- returns_error stdout ----\nError: \"something went wrong\""));
+    assert!(out.contains("---- panics stdout ----\nthread 'panics' panicked at "));
+    assert!(out.contains("oh no"));
+    assert!(out.contains("failures:\n    panics\n    returns_error\n"));
+    assert!(out.contains(
+        "test result: FAILED. 2 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out;"
+    ));
+}
+
+#[test]
+fn test_filter() {
+    let (conclusion, out) = run_with(&["passes"]);
+    assert_eq!(conclusion.passed, 2);
+    assert_eq!(conclusion.filtered_out, 2);
+    assert!(out.contains("test result: ok."));
+
+    let (conclusion, _) = run_with(&["passes", "--exact"]);
+    assert_eq!(conclusion.passed, 1);
+    assert_eq!(conclusion.filtered_out, 3);
+
+    let (conclusion, _) = run_with(&["--skip", "passes"]);
+    assert_eq!(conclusion.failed, 2);
+    assert_eq!(conclusion.filtered_out, 2);
+}
+
+#[test]
+fn test_list() {
+    let (conclusion, out) = run_with(&["--list", "pass"]);
+
+    assert_eq!(conclusion.passed, 0);
+    assert_eq!(
+        out,
+        "passes: test\npasses_too: test\n\n2 tests, 0 benchmarks\n"
+    );
+}
+
+#[test]
+fn test_ignored() {
+    let tests = || {
+        check::iter_named().map(|(name, func)| Test::new(name, func).with_ignored(name == "panics"))
+    };
+
+    let mut out = Vec::new();
+    let args = Arguments::parse(vec!["--format=terse", "--test-threads=1"]).unwrap();
+    let conclusion = run(&args, tests(), &mut out).unwrap();
+    assert_eq!(conclusion.ignored, 1);
+    assert_eq!(conclusion.failed, 1);
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .starts_with("\nrunning 4 tests\ni"));
+
+    let args = Arguments::parse(vec!["--ignored"]).unwrap();
+    let conclusion = run(&args, tests(), Vec::new()).unwrap();
+    assert_eq!(conclusion.failed, 1);
+    assert_eq!(conclusion.filtered_out, 3);
+
+    let args = Arguments::parse(vec!["--include-ignored"]).unwrap();
+    let conclusion = run(&args, tests(), Vec::new()).unwrap();
+    assert_eq!(conclusion.failed, 2);
+    assert_eq!(conclusion.ignored, 0);
+}
+
+#[test]
+fn test_json() {
+    let (_, out) = run_with(&[
+        "--format",
+        "json",
+        "-Z",
+        "unstable-options",
+        "returns_error",
+    ]);
+    let lines: Vec<_> = out.lines().collect();
+
+    assert_eq!(
+        lines[..3],
+        [
+            r#"{ "type": "suite", "event": "started", "test_count": 1 }"#,
+            r#"{ "type": "test", "event": "started", "name": "returns_error" }"#,
+            r#"{ "type": "test", "name": "returns_error", "event": "failed", "stdout": "Error: \"something went wrong\"" }"#,
+        ]
+    );
+    assert!(lines[3].starts_with(
+        r#"{ "type": "suite", "event": "failed", "passed": 0, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 3, "exec_time": "#
+    ));
+}
+
+#[test]
+fn test_arguments() {
+    let args = Arguments::parse(vec!["a", "-q", "--test-threads=3", "b"]).unwrap();
+    assert_eq!(args.filters, vec!["a", "b"]);
+    assert_eq!(args.format, Format::Terse);
+    assert_eq!(args.test_threads, Some(3));
+
+    assert!(Arguments::parse(vec!["--unknown"]).is_err());
+    assert!(Arguments::parse(vec!["--format", "xml"]).is_err());
+    assert!(Arguments::parse(vec!["--test-threads"]).is_err());
+}
@@ -0,0 +1,23 @@
+use label::create_label;
+
+create_label!(
+    fn check() -> Result<(), String>;
+);
+
+#[check::label]
+fn parse_number() -> Result<(), String> {
+    let number: usize = "42".parse().map_err(|e| format!("{}", e))?;
+    assert_eq!(number, 42);
+    Ok(())
+}
+
+#[check::label(ignore)]
+fn expensive() -> Result<(), String> {
+    let sum: usize = (0..1_000_000).sum();
+    assert_eq!(sum, 499_999_500_000);
+    Ok(())
+}
+
+fn main() {
+    label::harness::main(check::iter_entries());
+}
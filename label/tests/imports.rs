@@ -0,0 +1,80 @@
+use label::flags::{self as feature_flags, Flag};
+
+mod labels {
+    use label::create_label;
+
+    create_label!(
+        pub fn handlers() -> u32;
+    );
+
+    pub mod nested {
+        pub use super::handlers as reexported;
+    }
+}
+
+use labels::handlers as aliased;
+pub use labels::nested::reexported;
+
+#[aliased::label]
+fn via_alias() -> u32 {
+    1
+}
+
+#[reexported::label(key = "value")]
+fn via_reexport() -> u32 {
+    2
+}
+
+#[labels::nested::reexported::label]
+fn via_reexport_path() -> u32 {
+    3
+}
+
+#[crate::labels::handlers::label]
+fn via_crate_path() -> u32 {
+    4
+}
+
+mod inner {
+    use super::labels::handlers as local;
+
+    #[local::label]
+    pub fn in_module() -> u32 {
+        5
+    }
+}
+
+#[test]
+fn test_aliases_register_once() {
+    let mut registered: Vec<_> = labels::handlers::iter_named()
+        .map(|(name, f)| (name, f()))
+        .collect();
+    registered.sort_unstable();
+
+    assert_eq!(
+        registered,
+        [
+            ("in_module", 5),
+            ("via_alias", 1),
+            ("via_crate_path", 4),
+            ("via_reexport", 2),
+            ("via_reexport_path", 3),
+        ]
+    );
+    assert_eq!(reexported::iter().count(), 5);
+    assert_eq!(
+        aliased::iter_entries()
+            .find(|i| i.name() == "via_reexport")
+            .and_then(|i| i.meta("key")),
+        Some("value")
+    );
+}
+
+// a label from another crate, through an alias of its re-export.
+#[feature_flags::label]
+static ALIASED_FLAG: Flag = Flag::on();
+
+#[test]
+fn test_alias_across_crates() {
+    assert_eq!(feature_flags::list(), [("ALIASED_FLAG", true)]);
+}
@@ -0,0 +1,118 @@
+use label::create_label;
+use label::init::{self, sort, InitError};
+use std::sync::Mutex;
+
+static RAN: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+#[init::label(before = "http")]
+fn db() {
+    RAN.lock().unwrap().push("db");
+}
+
+#[init::label(after = "db", before = "http")]
+fn cache() {
+    RAN.lock().unwrap().push("cache");
+}
+
+#[init::label]
+fn http() {
+    RAN.lock().unwrap().push("http");
+}
+
+#[init::label(after = "cache, db", after = "http")]
+fn workers() {
+    RAN.lock().unwrap().push("workers");
+}
+
+#[label::init::label]
+fn config() {
+    RAN.lock().unwrap().push("config");
+}
+
+#[test]
+fn test_run_all() {
+    let order: Vec<_> = init::order().unwrap().iter().map(|i| i.name()).collect();
+    assert_eq!(order, vec!["config", "db", "cache", "http", "workers"]);
+
+    init::run_all().unwrap();
+    assert_eq!(*RAN.lock().unwrap(), order);
+}
+
+create_label!(
+    fn cyclic() -> ();
+    fn missing() -> ();
+    fn duplicate() -> ();
+);
+
+#[cyclic::label(after = "c")]
+fn a() {}
+
+#[cyclic::label(after = "a")]
+fn b() {}
+
+#[cyclic::label(after = "b")]
+fn c() {}
+
+#[cyclic::label]
+fn d() {}
+
+#[missing::label(after = "nothing")]
+fn incomplete() {}
+
+#[duplicate::label]
+fn same() {}
+
+mod other {
+    #[super::duplicate::label]
+    fn same() {}
+}
+
+#[test]
+fn test_cycle() {
+    let hooks = match sort(cyclic::iter_entries()).unwrap_err() {
+        InitError::Cycle(hooks) => hooks,
+        other => panic!("unexpected error: {}", other),
+    };
+
+    let mut names: Vec<_> = hooks.iter().map(|i| i.name).collect();
+    assert_eq!(names.len(), 3);
+    // the cycle can start at any of its hooks
+    while names[0] != "a" {
+        names.rotate_left(1);
+    }
+    assert_eq!(names, vec!["a", "b", "c"]);
+
+    let message = InitError::Cycle(hooks).to_string();
+    assert!(message.starts_with("hooks depend on each other in a cycle: `"));
+    assert!(message.contains("(at label/tests/init.rs:"));
+}
+
+#[test]
+fn test_missing() {
+    let error = sort(missing::iter_entries()).unwrap_err();
+    match &error {
+        InitError::MissingDependency { hook, dependency } => {
+            assert_eq!(hook.name, "incomplete");
+            assert_eq!(*dependency, "nothing");
+            assert_eq!(hook.location.file(), "label/tests/init.rs");
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let location = missing::iter_entries().next().unwrap().location();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "hook `incomplete` (at {}) depends on `nothing`, which doesn't exist",
+            location
+        )
+    );
+}
+
+#[test]
+fn test_duplicate() {
+    assert!(matches!(
+        sort(duplicate::iter_entries()).unwrap_err(),
+        InitError::DuplicateName(a, b) if a.name == "same" && b.name == "same"
+    ));
+}
@@ -0,0 +1,146 @@
+use label::create_label;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+create_label!(
+    fn generated() -> u32;
+    fn other() -> u32;
+);
+
+macro_rules! in_body {
+    ($name:ident, $value:expr) => {
+        #[generated::label]
+        fn $name() -> u32 {
+            $value
+        }
+    };
+}
+
+in_body!(in_body_fn, 1);
+
+macro_rules! with_attributes {
+    ($($(#[$meta:meta])* fn $name:ident() -> $value:expr;)*) => {
+        $(
+            $(#[$meta])*
+            fn $name() -> u32 {
+                $value
+            }
+        )*
+    };
+}
+
+with_attributes! {
+    #[generated::label]
+    fn meta_fn() -> 2;
+    #[other::label]
+    fn other_fn() -> 3;
+    #[other::label]
+    #[generated::label]
+    fn both_fn() -> 4;
+}
+
+macro_rules! with_path {
+    ($path:path, $name:ident, $value:expr) => {
+        #[$path]
+        fn $name() -> u32 {
+            $value
+        }
+    };
+}
+
+with_path!(generated::label, path_fn, 5);
+
+macro_rules! with_item {
+    ($item:item) => {
+        $item
+    };
+}
+
+with_item!(
+    #[generated::label]
+    fn item_fn() -> u32 {
+        6
+    }
+);
+
+// the label path isn't in the source code, so `labeled` is used.
+macro_rules! with_label {
+    ($label:ident, $name:ident, $value:expr) => {
+        #[label::labeled($label, generated_by = "with_label")]
+        fn $name() -> u32 {
+            $value
+        }
+    };
+}
+
+with_label!(generated, labeled_fn, 7);
+
+#[test]
+fn test_macro_rules() {
+    let mut generated: Vec<_> = generated::iter_named().map(|(n, f)| (n, f())).collect();
+    generated.sort();
+    let mut other: Vec<_> = other::iter_named().map(|(n, f)| (n, f())).collect();
+    other.sort();
+
+    assert_eq!(
+        generated,
+        [
+            ("both_fn", 4),
+            ("in_body_fn", 1),
+            ("item_fn", 6),
+            ("labeled_fn", 7),
+            ("meta_fn", 2),
+            ("path_fn", 5),
+            ("with_cfg_attr", 8),
+            ("with_cfg_attr_metadata", 9),
+        ]
+    );
+    assert_eq!(other, [("both_fn", 4), ("other_fn", 3)]);
+
+    let entry = generated::iter_entries()
+        .find(|i| i.name() == "labeled_fn")
+        .unwrap();
+    assert_eq!(entry.meta("generated_by"), Some("with_label"));
+}
+
+#[cfg_attr(all(), generated::label)]
+fn with_cfg_attr() -> u32 {
+    8
+}
+
+#[cfg_attr(all(), allow(dead_code), generated::label(key = "value"))]
+fn with_cfg_attr_metadata() -> u32 {
+    9
+}
+
+#[test]
+fn test_cfg_attr() {
+    let entry = generated::iter_entries()
+        .find(|i| i.name() == "with_cfg_attr_metadata")
+        .unwrap();
+    assert_eq!((entry.item())(), 9);
+    assert_eq!(entry.meta("key"), Some("value"));
+}
+
+create_label!(
+    fn tests() -> ();
+);
+
+static LABELED_TEST_RAN: AtomicBool = AtomicBool::new(false);
+
+// `#[test]` is expanded first, and keeps the label attribute on the function.
+#[test]
+#[tests::label]
+fn labeled_test() {
+    LABELED_TEST_RAN.store(true, Ordering::SeqCst);
+}
+
+#[test]
+fn test_other_attribute_macros() {
+    let names: Vec<_> = tests::iter_named().map(|(n, _)| n).collect();
+    assert_eq!(names, ["labeled_test"]);
+
+    for i in tests::iter() {
+        i();
+    }
+    assert!(LABELED_TEST_RAN.load(Ordering::SeqCst));
+}
@@ -6,7 +6,7 @@ use std::collections::HashSet;
 // Create two label.
 create_label!(
     // test that comments work here
-    // V test that pub works (does nothing)
+    // V test that visibility works, labels are private like other items by default
     pub(self) fn test() -> &'static str;
     fn test2(usize) -> usize;
 );
@@ -35,7 +35,7 @@ pub mod folder {
             "Test3!"
         }
 
-        create_label!(fn test1() -> &'static str);
+        create_label!(pub fn test1() -> &'static str);
     }
 }
 
@@ -125,6 +125,38 @@ fn test_with_generics() {
     }
 }
 
+create_label!(
+    fn static_str(&'static str) -> usize;
+    fn default_number() -> u32;
+    fn field<'a, 'b>(Test<'a, 'b>) -> &'a usize;
+);
+
+// more general than the label, which labels can still coerce to.
+#[static_str::label]
+fn any_str(s: &str) -> usize {
+    s.len()
+}
+
+#[field::label]
+fn static_usize(_: Test) -> &'static usize {
+    &20
+}
+
+// generic functions get their type parameters from the label.
+#[default_number::label]
+fn generic_default<T: Default>() -> T {
+    T::default()
+}
+
+#[test]
+fn test_coercions() {
+    assert_eq!(static_str::iter().map(|i| i("abc")).sum::<usize>(), 3);
+    assert_eq!(default_number::iter().map(|i| i()).sum::<u32>(), 0);
+    for i in field::iter() {
+        assert_eq!(i(Test { a: &10, _b: &15 }), &20);
+    }
+}
+
 #[test]
 fn test_traits() {
     fn implements<T: Send + Sync + Any>(_value: T) {}
@@ -139,3 +171,106 @@ fn test_traits() {
         implements(i);
     }
 }
+
+create_label!(
+    fn documented() -> ();
+    const documented_const: usize;
+);
+
+/// A labeled function.
+///
+/// With a second paragraph,
+///     and some indentation.
+#[documented::label]
+fn documented_fn() {}
+
+#[documented::label]
+fn undocumented_fn() {}
+
+/**
+   A block comment
+   on two lines.
+*/
+#[documented::label]
+fn block_documented_fn() {}
+
+/// A labeled constant.
+#[documented_const::label]
+const DOCUMENTED: usize = 42;
+
+#[test]
+fn test_doc() {
+    let mut docs = std::collections::HashMap::new();
+    for entry in documented::iter_entries() {
+        docs.insert(entry.name(), entry.doc());
+        entry.item()();
+    }
+
+    assert_eq!(docs.len(), 4);
+    assert_eq!(
+        docs["documented_fn"],
+        "A labeled function.\n\nWith a second paragraph,\n    and some indentation."
+    );
+    assert_eq!(docs["undocumented_fn"], "");
+    assert_eq!(
+        docs["block_documented_fn"],
+        "A block comment\non two lines."
+    );
+
+    let entry = documented_const::iter_entries().next().unwrap();
+    assert_eq!(entry.name(), "DOCUMENTED");
+    assert_eq!(entry.doc(), "A labeled constant.");
+    assert_eq!(*entry.item(), 42);
+}
+
+create_label!(
+    fn with_metadata() -> ();
+);
+
+#[with_metadata::label(ignore, method = "GET", retries = 3, enabled = true)]
+#[documented::label(first)]
+fn fn_with_metadata() {}
+
+#[test]
+fn test_metadata() {
+    let entry = with_metadata::iter_entries().next().unwrap();
+
+    assert_eq!(
+        entry.metadata(),
+        &[
+            ("ignore", ""),
+            ("method", "GET"),
+            ("retries", "3"),
+            ("enabled", "true")
+        ]
+    );
+    assert_eq!(entry.meta("method"), Some("GET"));
+    assert_eq!(entry.meta("ignore"), Some(""));
+    assert_eq!(entry.meta("missing"), None);
+
+    let entry = documented::iter_entries()
+        .find(|i| i.name() == "fn_with_metadata")
+        .unwrap();
+    assert_eq!(entry.metadata(), &[("first", "")]);
+}
+
+create_label!(
+    fn located() -> ();
+);
+
+#[located::label]
+fn located_fn() {}
+const LOCATED_LINE: u32 = line!() - 1;
+
+#[test]
+fn test_location() {
+    let location = located::iter_entries().next().unwrap().location();
+
+    assert_eq!(location.file(), "label/tests/main.rs");
+    assert_eq!(location.line(), LOCATED_LINE);
+    assert_eq!(location.column(), 4);
+    assert_eq!(
+        location.to_string(),
+        format!("label/tests/main.rs:{}:4", LOCATED_LINE)
+    );
+}
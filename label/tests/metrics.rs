@@ -0,0 +1,92 @@
+use label::metrics::{self, Counter, Gauge, Histogram, Kind, Metric};
+
+/// Requests handled since starting.
+#[metrics::label]
+static REQUESTS: Counter = Counter::new();
+
+/// Connections which are currently open.
+#[metrics::label(name = "open_connections")]
+static CONNECTIONS: Gauge = Gauge::new();
+
+/// Time taken to handle a request,
+/// in seconds.
+#[metrics::label]
+static LATENCY: Histogram<3> = Histogram::new([0.1, 0.5, 1.0]);
+
+/// Requests which failed.
+#[label::metrics::label]
+static ERRORS: Counter = Counter::new();
+
+/// A custom metric.
+struct Registered;
+
+impl Metric for Registered {
+    fn kind(&self) -> Kind {
+        Kind::Gauge
+    }
+
+    fn write_samples(&self, name: &str, out: &mut String) {
+        out.push_str(&format!("{} {}\n", name, metrics::iter().count()));
+    }
+}
+
+/// The number of labeled metrics, as a custom metric.
+#[metrics::label]
+static REGISTERED: Registered = Registered;
+
+#[test]
+fn test_render_prometheus() {
+    REQUESTS.inc();
+    REQUESTS.inc_by(2);
+    CONNECTIONS.inc();
+    CONNECTIONS.add(1.5);
+    CONNECTIONS.dec();
+    for i in [0.05, 0.3, 0.3, 0.7, 3.0] {
+        LATENCY.observe(i);
+    }
+
+    assert_eq!(REQUESTS.get(), 3);
+    assert_eq!(ERRORS.get(), 0);
+    assert_eq!(CONNECTIONS.get(), 1.5);
+    assert_eq!(LATENCY.buckets(), [(0.1, 1), (0.5, 3), (1.0, 4)]);
+    assert_eq!(LATENCY.count(), 5);
+
+    assert_eq!(
+        metrics::render_prometheus(),
+        "\
+# HELP errors Requests which failed.
+# TYPE errors counter
+errors 0
+# HELP latency Time taken to handle a request,\\nin seconds.
+# TYPE latency histogram
+latency_bucket{le=\"0.1\"} 1
+latency_bucket{le=\"0.5\"} 3
+latency_bucket{le=\"1\"} 4
+latency_bucket{le=\"+Inf\"} 5
+latency_sum 4.35
+latency_count 5
+# HELP open_connections Connections which are currently open.
+# TYPE open_connections gauge
+open_connections 1.5
+# HELP registered The number of labeled metrics, as a custom metric.
+# TYPE registered gauge
+registered 5
+# HELP requests Requests handled since starting.
+# TYPE requests counter
+requests 3
+"
+    );
+}
+
+#[test]
+fn test_gauge_special_values() {
+    let gauge = Gauge::new();
+    let mut out = String::new();
+
+    gauge.set(f64::INFINITY);
+    gauge.write_samples("gauge", &mut out);
+    gauge.set(f64::NAN);
+    gauge.write_samples("gauge", &mut out);
+
+    assert_eq!(out, "gauge +Inf\ngauge NaN\n");
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "rayon")]
+
+use label::create_label;
+use label::rayon::prelude::*;
+
+create_label!(
+    fn check(usize) -> usize;
+    static value: usize;
+);
+
+#[check::label]
+fn double(x: usize) -> usize {
+    x * 2
+}
+
+#[check::label]
+fn square(x: usize) -> usize {
+    x * x
+}
+
+#[check::label]
+fn increment(x: usize) -> usize {
+    x + 1
+}
+
+#[value::label]
+static A: usize = 3;
+
+#[test]
+fn test_par_iter() {
+    let sequential: Vec<_> = check::iter().map(|i| i(5)).collect();
+    let parallel: Vec<_> = check::par_iter().map(|i| i(5)).collect();
+
+    assert_eq!(parallel.len(), 3);
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn test_par_iter_named() {
+    let sequential: Vec<_> = check::iter_named().map(|(name, i)| (name, i(5))).collect();
+    let parallel: Vec<_> = check::par_iter_named()
+        .map(|(name, i)| (name, i(5)))
+        .collect();
+
+    assert!(parallel.contains(&("double", 10)));
+    assert!(parallel.contains(&("square", 25)));
+    assert!(parallel.contains(&("increment", 6)));
+    assert_eq!(sequential, parallel);
+}
+
+#[test]
+fn test_par_iter_static() {
+    let values: Vec<_> = value::par_iter().copied().collect();
+    assert_eq!(values, vec![3]);
+}
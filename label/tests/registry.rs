@@ -0,0 +1,85 @@
+use label::registry;
+
+#[registry]
+pub trait Plugin {
+    fn name(&self) -> &str;
+}
+
+/// Writes everything down.
+#[plugin::label(order = 1)]
+struct Logger;
+
+impl Plugin for Logger {
+    fn name(&self) -> &str {
+        "logger"
+    }
+}
+
+struct Metrics;
+
+#[plugin::label]
+impl Plugin for Metrics {
+    fn name(&self) -> &str {
+        "metrics"
+    }
+}
+
+mod nested {
+    pub struct Tracer;
+}
+
+#[plugin::label]
+impl Plugin for nested::Tracer {
+    fn name(&self) -> &str {
+        "tracer"
+    }
+}
+
+#[registry(exporters)]
+trait HttpExporter {
+    fn export(&self) -> u32;
+}
+
+#[exporters::label]
+struct Json;
+
+impl HttpExporter for Json {
+    fn export(&self) -> u32 {
+        1
+    }
+}
+
+#[registry]
+trait HTTPHandler {}
+
+#[http_handler::label]
+struct Index;
+
+impl HTTPHandler for Index {}
+
+#[test]
+fn test_registry() {
+    let mut names: Vec<_> = plugin::iter().map(|i| i.name()).collect();
+    names.sort();
+    assert_eq!(names, vec!["logger", "metrics", "tracer"]);
+
+    let mut items: Vec<_> = plugin::iter_named().map(|(name, _)| name).collect();
+    items.sort();
+    assert_eq!(items, vec!["Logger", "Metrics", "Tracer"]);
+
+    let logger = plugin::iter_entries()
+        .find(|i| i.name() == "Logger")
+        .unwrap();
+    assert_eq!(logger.doc(), "Writes everything down.");
+    assert_eq!(logger.meta("order"), Some("1"));
+
+    // registered items can be used as `&dyn Plugin`.
+    let plugin: &dyn Plugin = logger.item();
+    assert_eq!(plugin.name(), "logger");
+
+    assert_eq!(
+        exporters::iter().map(|i| i.export()).collect::<Vec<_>>(),
+        vec![1]
+    );
+    assert_eq!(http_handler::iter().count(), 1);
+}
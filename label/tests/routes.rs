@@ -0,0 +1,191 @@
+#![cfg(feature = "routes")]
+
+use label::create_label;
+use label::routes::{route, NotFound, Request, Response, RouteError, Router};
+
+#[route::label(method = "GET", path = "/users")]
+fn list_users(_request: &Request) -> Response {
+    Response::ok("all users")
+}
+
+#[route::label(method = "GET", path = "/users/:id")]
+fn get_user(request: &Request) -> Response {
+    Response::ok(format!("user {}", request.param("id").unwrap()))
+}
+
+#[route::label(method = "post", path = "/users/:id/posts")]
+fn create_post(request: &Request) -> Response {
+    Response::new(
+        201,
+        format!("post by {}: {}", request.param("id").unwrap(), request.body),
+    )
+}
+
+#[route::label(method = "GET", path = "/users/me")]
+fn current_user(_request: &Request) -> Response {
+    Response::ok("me")
+}
+
+#[test]
+fn test_dispatch() {
+    let router = Router::from_label().unwrap();
+
+    assert_eq!(
+        router.dispatch(Request::new("GET", "/users")),
+        Response::ok("all users")
+    );
+    assert_eq!(
+        router.dispatch(Request::new("GET", "/users/42/")),
+        Response::ok("user 42")
+    );
+    assert_eq!(
+        router.dispatch(Request::new("GET", "/users/me")),
+        Response::ok("me")
+    );
+    assert_eq!(
+        router.dispatch(Request::new("POST", "/users/me/posts").with_body("hello")),
+        Response::new(201, "post by me: hello")
+    );
+    assert_eq!(router.dispatch(Request::new("GET", "/posts")).status, 404);
+    assert_eq!(
+        router.dispatch(Request::new("GET", "/users/1/2")).status,
+        404
+    );
+    assert_eq!(
+        router.dispatch(Request::new("DELETE", "/users")).status,
+        405
+    );
+}
+
+#[test]
+fn test_find() {
+    let router = Router::from_entries(route::iter_entries()).unwrap();
+
+    let found = router.find("GET", "/users/42").unwrap();
+    assert_eq!(found.name, "get_user");
+    assert_eq!(found.params["id"], "42");
+
+    assert_eq!(router.find("GET", "/").unwrap_err(), NotFound::Path);
+    assert_eq!(
+        router.find("PUT", "/users/me").unwrap_err(),
+        NotFound::Method
+    );
+
+    let routes: Vec<_> = router
+        .routes()
+        .into_iter()
+        .map(|(method, path, route)| (method, path, route.name))
+        .collect();
+    assert_eq!(
+        routes,
+        vec![
+            ("GET", "/users", "list_users"),
+            ("GET", "/users/:id", "get_user"),
+            ("POST", "/users/:id/posts", "create_post"),
+            ("GET", "/users/me", "current_user"),
+        ]
+    );
+}
+
+// routes can be built from any label with `method` and `path` arguments
+create_label!(
+    fn conflicting() -> &'static str;
+    fn renamed() -> &'static str;
+    fn incomplete() -> &'static str;
+    fn shadowed() -> &'static str;
+);
+
+#[conflicting::label(method = "GET", path = "/a/:id")]
+fn first() -> &'static str {
+    "first"
+}
+
+#[conflicting::label(method = "GET", path = "/a/:id/")]
+fn second() -> &'static str {
+    "second"
+}
+
+#[renamed::label(method = "GET", path = "/a/:id")]
+fn by_id() -> &'static str {
+    "by_id"
+}
+
+#[renamed::label(method = "DELETE", path = "/a/:name")]
+fn by_name() -> &'static str {
+    "by_name"
+}
+
+#[incomplete::label(method = "GET")]
+fn no_path() -> &'static str {
+    "no_path"
+}
+
+#[shadowed::label(method = "GET", path = "/users/:id")]
+fn shadowed_get() -> &'static str {
+    "get"
+}
+
+#[shadowed::label(method = "POST", path = "/users/me")]
+fn shadowed_post() -> &'static str {
+    "post"
+}
+
+#[test]
+fn test_parameter_fallback() {
+    let router = Router::from_entries(shadowed::iter_entries()).unwrap();
+
+    let found = router.find("GET", "/users/me").unwrap();
+    assert_eq!(found.name, "shadowed_get");
+    assert_eq!(found.params["id"], "me");
+    assert_eq!(
+        router.find("POST", "/users/me").unwrap().name,
+        "shadowed_post"
+    );
+    assert_eq!(
+        router.find("POST", "/users/42").unwrap_err(),
+        NotFound::Method
+    );
+    assert_eq!(router.find("GET", "/posts").unwrap_err(), NotFound::Path);
+}
+
+#[test]
+fn test_conflicts() {
+    match Router::from_entries(conflicting::iter_entries()).unwrap_err() {
+        RouteError::Conflict {
+            method,
+            first,
+            second,
+            ..
+        } => {
+            assert_eq!(method, "GET");
+            let mut names = [first, second];
+            names.sort_unstable();
+            assert_eq!(names, ["first", "second"]);
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    assert!(matches!(
+        Router::from_entries(renamed::iter_entries()).unwrap_err(),
+        RouteError::ParameterName { .. }
+    ));
+
+    let error = Router::from_entries(incomplete::iter_entries()).unwrap_err();
+    assert_eq!(
+        error,
+        RouteError::MissingMetadata {
+            handler: "no_path",
+            key: "path"
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "handler `no_path` has no `path` argument"
+    );
+
+    let mut router = Router::new();
+    assert!(router.add("GET", "users", "relative", no_path).is_err());
+    assert!(router.add("GET", "/users/:", "unnamed", no_path).is_err());
+    router.add("GET", "/", "root", no_path).unwrap();
+    assert_eq!((router.find("GET", "").unwrap().handler)(), "no_path");
+}
@@ -0,0 +1,91 @@
+use label::create_label;
+use label::teardown::{self, Status};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+static RAN: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+#[teardown::label]
+fn first() {
+    RAN.lock().unwrap().push("first");
+}
+
+#[teardown::label]
+fn second() {
+    RAN.lock().unwrap().push("second");
+}
+
+#[label::teardown::label]
+fn third() {
+    RAN.lock().unwrap().push("third");
+}
+
+#[test]
+fn test_guard() {
+    let registered: Vec<_> = teardown::iter_named().map(|(name, _)| name).collect();
+    let mut expected = registered.clone();
+    expected.reverse();
+
+    let guard = teardown::run_all();
+    assert!(RAN.lock().unwrap().is_empty());
+    drop(guard);
+    assert_eq!(*RAN.lock().unwrap(), expected);
+
+    // hooks only run once, not again from a second guard or at exit.
+    let statuses = teardown::run_all().finish();
+    assert!(statuses.is_empty());
+    assert_eq!(RAN.lock().unwrap().len(), 3);
+}
+
+create_label!(
+    fn hooks() -> ();
+);
+
+static HOOKS_RAN: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+#[hooks::label]
+fn before_panic() {
+    HOOKS_RAN.lock().unwrap().push("before_panic");
+}
+
+#[hooks::label]
+fn panics() {
+    panic!("could not flush");
+}
+
+#[hooks::label(timeout_ms = 50)]
+fn slow() {
+    thread::sleep(Duration::from_secs(2));
+}
+
+#[hooks::label]
+fn after_slow() {
+    HOOKS_RAN.lock().unwrap().push("after_slow");
+}
+
+#[test]
+fn test_isolation() {
+    let mut entries: Vec<_> = hooks::iter_entries().collect();
+    entries.sort_by_key(|i| {
+        ["before_panic", "panics", "slow", "after_slow"]
+            .iter()
+            .position(|j| *j == i.name())
+    });
+
+    let statuses = teardown::run(entries);
+
+    assert_eq!(
+        statuses,
+        vec![
+            ("after_slow", Status::Finished),
+            ("slow", Status::TimedOut(Duration::from_millis(50))),
+            ("panics", Status::Panicked("could not flush".to_string())),
+            ("before_panic", Status::Finished),
+        ]
+    );
+    assert_eq!(
+        *HOOKS_RAN.lock().unwrap(),
+        vec!["after_slow", "before_panic"]
+    );
+}
@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+
+use label::create_label;
+
+create_label!(
+    #[deprecated(note = "use `handlers` instead")]
+    fn old_handlers() -> ();
+);
+
+#[old_handlers::label]
+fn a() {}
+
+fn main() {}
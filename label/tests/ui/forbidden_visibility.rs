@@ -0,0 +1,16 @@
+use label::create_label;
+
+create_label!(
+    #[label(require = pub)]
+    fn exported() -> ();
+    #[label(forbid = pub)]
+    static internal: u32;
+);
+
+#[exported::label]
+fn a() {}
+
+#[internal::label]
+pub static B: u32 = 0;
+
+fn main() {}
@@ -0,0 +1,7 @@
+use label::create_label;
+
+create_label!(
+    fn generic<T>(T) -> ();
+);
+
+fn main() {}
@@ -0,0 +1,12 @@
+use label::create_label;
+
+create_label!(fn hooks() -> ());
+
+mod plugin {
+    use super::hooks::label;
+
+    #[label]
+    fn a() {}
+}
+
+fn main() {}
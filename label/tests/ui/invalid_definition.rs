@@ -0,0 +1,7 @@
+use label::create_label;
+
+create_label!(
+    struct Test;
+);
+
+fn main() {}
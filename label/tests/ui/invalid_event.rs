@@ -0,0 +1,7 @@
+use label::create_label;
+
+create_label!(
+    event on_start();
+);
+
+fn main() {}
@@ -0,0 +1,8 @@
+use label::create_label;
+
+create_label!(fn test() -> ());
+
+#[test::label(some::name = "value")]
+fn a() {}
+
+fn main() {}
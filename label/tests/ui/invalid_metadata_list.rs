@@ -0,0 +1,8 @@
+use label::create_label;
+
+create_label!(fn test() -> ());
+
+#[test::label("value")]
+fn a() {}
+
+fn main() {}
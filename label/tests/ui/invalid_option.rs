@@ -0,0 +1,23 @@
+use label::create_label;
+
+create_label!(
+    #[label(attribute = add)]
+    fn test() -> ();
+);
+
+create_label!(
+    #[label(name = register)]
+    fn other() -> ();
+);
+
+create_label!(
+    #[label(require = pub(in crate::a))]
+    fn restricted() -> ();
+);
+
+create_label!(
+    #[label(require = pub, forbid = pub)]
+    fn nothing() -> ();
+);
+
+fn main() {}
@@ -0,0 +1,18 @@
+use label::create_label;
+
+create_label!(
+    event on_start(u32);
+    fn tasks() -> ();
+);
+
+#[on_start::label(priority = "high")]
+fn a(_: &u32) {}
+
+#[on_start::label(priority = 10)]
+fn b(_: &u32) {}
+
+// only event labels use `priority`
+#[tasks::label(priority = "high")]
+fn c() {}
+
+fn main() {}
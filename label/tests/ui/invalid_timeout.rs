@@ -0,0 +1,12 @@
+use label::teardown;
+
+#[teardown::label(timeout_ms = "abc")]
+fn a() {}
+
+#[teardown::label(timeout_ms = -1)]
+fn b() {}
+
+#[teardown::label(timeout_ms = 500)]
+fn c() {}
+
+fn main() {}
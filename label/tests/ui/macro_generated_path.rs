@@ -0,0 +1,14 @@
+use label::create_label;
+
+create_label!(fn test() -> ());
+
+macro_rules! labeled {
+    ($label:ident, $name:ident) => {
+        #[$label::label]
+        fn $name() {}
+    };
+}
+
+labeled!(test, a);
+
+fn main() {}
@@ -0,0 +1,29 @@
+// trybuild builds this as a binary, without `cfg(test)`, unlike `tests/cfg.rs`.
+use label::create_label;
+
+create_label!(
+    fn configured() -> u32;
+);
+
+#[cfg(test)]
+#[configured::label]
+fn only_in_tests() -> u32 {
+    1
+}
+
+#[cfg(not(test))]
+#[configured::label]
+fn never_in_tests() -> u32 {
+    2
+}
+
+#[cfg_attr(test, configured::label)]
+#[allow(dead_code)]
+fn labeled_in_tests() -> u32 {
+    3
+}
+
+fn main() {
+    let names: Vec<_> = configured::iter_named().map(|(n, _)| n).collect();
+    assert_eq!(names, ["never_in_tests"]);
+}
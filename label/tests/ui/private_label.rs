@@ -0,0 +1,12 @@
+mod labels {
+    use label::create_label;
+
+    create_label!(fn hidden() -> ());
+}
+
+#[labels::hidden::label]
+fn a() {}
+
+fn main() {
+    labels::hidden::iter();
+}
@@ -0,0 +1,19 @@
+use label::registry;
+
+#[registry]
+trait Plugin {}
+
+#[plugin::label]
+struct Fields {
+    name: String,
+}
+
+struct Wrapper<T>(T);
+
+#[plugin::label]
+impl<T> Plugin for Wrapper<T> {}
+
+#[registry]
+trait Generic<T> {}
+
+fn main() {}
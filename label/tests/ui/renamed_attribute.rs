@@ -0,0 +1,10 @@
+use label::create_label;
+
+create_label!(fn test() -> ());
+
+use test::label as test_label;
+
+#[test_label]
+fn a() {}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use label::create_label;
+
+create_label!(
+    static mut counters: usize;
+    static values: usize;
+    fn tasks() -> ();
+);
+
+#[counters::label]
+#[values::label]
+static mut SHARED: usize = 0;
+
+#[tasks::label]
+static mut TASK: usize = 0;
+
+fn main() {}
@@ -0,0 +1,26 @@
+use label::create_label;
+
+create_label!(
+    fn test2(usize) -> usize;
+    fn handlers<'a>(&'a str) -> &'a str;
+    static numbers: u32;
+);
+
+#[test2::label]
+fn foo(_: u8) {}
+
+#[test2::label]
+fn wrong_return(x: usize) -> u8 {
+    x as u8
+}
+
+#[test2::label]
+#[handlers::label]
+fn both(x: usize) -> usize {
+    x
+}
+
+#[numbers::label]
+fn not_a_number() {}
+
+fn main() {}
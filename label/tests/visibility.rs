@@ -0,0 +1,123 @@
+use label::{create_label, Visibility};
+
+create_label!(
+    fn any() -> ();
+    #[label(require = pub)]
+    fn public() -> ();
+    #[label(forbid = pub, forbid = pub(crate))]
+    static internal: u32;
+);
+
+#[any::label]
+fn private_fn() {}
+
+#[any::label]
+pub fn public_fn() {}
+
+#[any::label]
+pub(crate) fn crate_fn() {}
+
+#[any::label]
+#[allow(clippy::needless_pub_self)]
+pub(self) fn self_fn() {}
+
+mod inner {
+    #[crate::any::label]
+    pub(super) fn super_fn() {}
+
+    pub mod nested {
+        #[crate::any::label]
+        pub(in crate::inner) fn restricted_fn() {}
+    }
+
+    #[crate::internal::label]
+    pub(super) static SHARED_LIMIT: u32 = 20;
+}
+
+#[public::label]
+pub fn exported() {}
+
+mod labels {
+    use label::create_label;
+
+    create_label!(
+        pub(crate) fn crate_label() -> u32;
+        pub(super) fn parent_label() -> u32;
+    );
+
+    pub mod nested {
+        use label::create_label;
+
+        create_label!(pub(in crate::labels) fn nested_label() -> u32);
+
+        #[nested_label::label]
+        fn in_nested() -> u32 {
+            3
+        }
+    }
+
+    #[nested::nested_label::label]
+    fn in_labels() -> u32 {
+        4
+    }
+
+    pub fn nested_values() -> Vec<u32> {
+        let mut res: Vec<_> = nested::nested_label::iter().map(|i| i()).collect();
+        res.sort();
+        res
+    }
+}
+
+#[labels::crate_label::label]
+fn with_crate_label() -> u32 {
+    1
+}
+
+#[labels::parent_label::label]
+fn with_parent_label() -> u32 {
+    2
+}
+
+#[internal::label]
+static LIMIT: u32 = 10;
+
+#[test]
+fn test_visibility() {
+    let mut visibilities: Vec<_> = any::iter_entries()
+        .map(|i| (i.name(), i.visibility()))
+        .collect();
+    visibilities.sort_by_key(|(name, _)| *name);
+    assert_eq!(
+        visibilities,
+        vec![
+            ("crate_fn", Visibility::Crate),
+            ("private_fn", Visibility::Private),
+            ("public_fn", Visibility::Public),
+            ("restricted_fn", Visibility::Restricted("crate::inner")),
+            ("self_fn", Visibility::Private),
+            ("super_fn", Visibility::Super),
+        ]
+    );
+    assert_eq!(
+        Visibility::Restricted("crate::inner").to_string(),
+        "pub(in crate::inner)"
+    );
+
+    assert_eq!(public::iter_named().count(), 1);
+
+    assert_eq!(
+        labels::crate_label::iter().map(|i| i()).collect::<Vec<_>>(),
+        vec![1]
+    );
+    assert_eq!(
+        labels::parent_label::iter()
+            .map(|i| i())
+            .collect::<Vec<_>>(),
+        vec![2]
+    );
+    assert_eq!(labels::nested_values(), vec![3, 4]);
+
+    let mut limits: Vec<_> = internal::iter().copied().collect();
+    limits.sort();
+    assert_eq!(limits, vec![10, 20]);
+}